@@ -0,0 +1,70 @@
+// Estimating the clock offset between two peers with `ClockSync`. Both peers share a process,
+// so the true offset is zero.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    clock_sync().await.unwrap();
+}
+
+async fn clock_sync() -> Result<(), rtc::Error> {
+    let server = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+    let client = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+
+    let (data_channel_sender, mut data_channel_receiver) = mpsc::unbounded();
+    server.on_data_channel(Box::new(move |data_channel| {
+        _ = data_channel_sender.unbounded_send(data_channel);
+        Box::pin(async {})
+    }));
+    let client_channel = client
+        .create_data_channel(rtc::ClockSync::LABEL, rtc::ClockSync::data_channel_init())
+        .await?;
+    let (open_sender, mut open_receiver) = mpsc::unbounded::<()>();
+    client_channel.on_open(Box::new(move || {
+        _ = open_sender.unbounded_send(());
+        Box::pin(async {})
+    }));
+
+    let timeout = Some(Duration::from_secs(5));
+    let offer = client.create_offer_complete(timeout).await?;
+    server.set_remote_description(&offer).await?;
+    let answer = server.create_answer_complete(timeout).await?;
+    client.set_remote_description(&answer).await?;
+    open_receiver.next().await;
+    let server_channel = data_channel_receiver.next().await.unwrap();
+
+    let server_clock =
+        rtc::ClockSync::new(server_channel).with_interval(Duration::from_millis(100));
+    let client_clock =
+        rtc::ClockSync::new(client_channel).with_interval(Duration::from_millis(100));
+    let syncing = futures::future::join(server_clock.run(), client_clock.run());
+    futures::future::select(
+        Box::pin(syncing),
+        Box::pin(tokasm::time::sleep(Duration::from_secs(2))),
+    )
+    .await;
+
+    let estimate = client_clock.estimate().unwrap();
+    info!(
+        "[client] Offset {}us, uncertainty {:?}, {} samples",
+        estimate.offset_micros, estimate.uncertainty, estimate.samples
+    );
+    let error = Duration::from_micros(estimate.offset_micros.unsigned_abs());
+    assert!(error <= estimate.uncertainty + Duration::from_millis(1));
+    info!(
+        "[client] Local {:?}, remote {:?}",
+        rtc::ClockSync::local_now(),
+        client_clock.remote_now().unwrap()
+    );
+
+    client.close().await?;
+    server.close().await?;
+    Ok(())
+}
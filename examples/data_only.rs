@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    data_only().await.unwrap();
+}
+
+async fn data_only() -> Result<(), rtc::Error> {
+    // no media codecs or RTP interceptors are set up for these peers
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            data_channels_only: true,
+            ..Default::default()
+        },
+    )?;
+    let peer1 = factory.create().await?;
+    let peer2 = factory.create().await?;
+
+    let (message_sender, mut message_receiver) = mpsc::unbounded::<Vec<u8>>();
+    peer2.on_data_channel(Box::new(move |data_channel| {
+        let message_sender = message_sender.clone();
+        data_channel.on_message(Box::new(move |message, _| {
+            _ = message_sender.unbounded_send(message);
+            Box::pin(async {})
+        }));
+        Box::pin(async {})
+    }));
+
+    let data_channel = peer1
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let (open_sender, mut open_receiver) = mpsc::unbounded::<()>();
+    data_channel.on_open(Box::new(move || {
+        _ = open_sender.unbounded_send(());
+        Box::pin(async {})
+    }));
+
+    // candidates are embedded in the descriptions, so none can arrive before the remote
+    // description is set
+    let offer = peer1
+        .create_offer_complete(Some(Duration::from_secs(5)))
+        .await?;
+    let media = rtc::sdp::Sdp::from_session_description(&offer)?
+        .media
+        .into_iter()
+        .map(|media| media.description.media)
+        .collect::<Vec<_>>();
+    info!("[peer1] Offered media: {:?}", media);
+    assert_eq!(media, ["application"]);
+
+    peer2.set_remote_description(&offer).await?;
+    let answer = peer2
+        .create_answer_complete(Some(Duration::from_secs(5)))
+        .await?;
+    peer1.set_remote_description(&answer).await?;
+
+    open_receiver.next().await;
+    data_channel.send(b"hello").await?;
+    let message = message_receiver.next().await.unwrap();
+    info!("[peer2] Received: {}", String::from_utf8_lossy(&message));
+    assert_eq!(message, b"hello");
+
+    peer1.close().await?;
+    peer2.close().await?;
+    Ok(())
+}
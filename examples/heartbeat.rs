@@ -0,0 +1,72 @@
+// Measuring round-trip times with a `Heartbeat`, and noticing when the remote peer goes away.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    heartbeat().await.unwrap();
+}
+
+async fn heartbeat() -> Result<(), rtc::Error> {
+    let server = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+    let client = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+
+    let (data_channel_sender, mut data_channel_receiver) = mpsc::unbounded();
+    server.on_data_channel(Box::new(move |data_channel| {
+        _ = data_channel_sender.unbounded_send(data_channel);
+        Box::pin(async {})
+    }));
+    let client_channel = client
+        .create_data_channel(rtc::Heartbeat::LABEL, rtc::Heartbeat::data_channel_init())
+        .await?;
+    let (open_sender, mut open_receiver) = mpsc::unbounded::<()>();
+    client_channel.on_open(Box::new(move || {
+        _ = open_sender.unbounded_send(());
+        Box::pin(async {})
+    }));
+
+    let timeout = Some(Duration::from_secs(5));
+    let offer = client.create_offer_complete(timeout).await?;
+    server.set_remote_description(&offer).await?;
+    let answer = server.create_answer_complete(timeout).await?;
+    client.set_remote_description(&answer).await?;
+    open_receiver.next().await;
+    let server_channel = data_channel_receiver.next().await.unwrap();
+
+    let server_heartbeat = rtc::Heartbeat::new(server_channel)
+        .with_interval(Duration::from_millis(200))
+        .with_timeout(Duration::from_secs(2));
+    let client_heartbeat = rtc::Heartbeat::new(client_channel)
+        .with_interval(Duration::from_millis(200))
+        .with_timeout(Duration::from_secs(2));
+
+    let heartbeats = futures::future::try_join(server_heartbeat.run(), client_heartbeat.run());
+    let measuring = tokasm::time::sleep(Duration::from_secs(3));
+    match futures::future::select(Box::pin(heartbeats), Box::pin(measuring)).await {
+        futures::future::Either::Left((result, _)) => result.map(|_| ())?,
+        futures::future::Either::Right(_) => {}
+    }
+    let stats = client_heartbeat.stats();
+    info!(
+        "[client] {} samples, mean {:?}, jitter {:?}, min {:?}",
+        stats.samples, stats.mean, stats.jitter, stats.min
+    );
+    assert!(stats.samples > 0);
+
+    // the server notices the client is gone once it has been silent for the timeout
+    client.close().await?;
+    assert_eq!(
+        server_heartbeat.run().await,
+        Err(rtc::Error::HeartbeatTimedOut)
+    );
+    info!("[server] Client timed out");
+
+    server.close().await?;
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::{sync::Arc, time::Duration};
+
+use tracing::{error, info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/whip", listener.local_addr().unwrap());
+    let server = rtc::http::HttpSignalingServer::new(
+        rtc::Configuration::default(),
+        Box::new(|peer| {
+            Box::pin(async move {
+                peer.on_data_channel(Box::new(|data_channel| {
+                    Box::pin(async move {
+                        let echo = data_channel.clone();
+                        data_channel.on_message(Box::new(move |message, _| {
+                            let echo = echo.clone();
+                            Box::pin(async move {
+                                info!("[server] Echoing {} bytes", message.len());
+                                if let Err(err) = echo.send(&message).await {
+                                    error!("[server] Failed to echo message: {:?}", err);
+                                }
+                            })
+                        }));
+                    })
+                }));
+            })
+        }),
+    );
+
+    let server_future = Box::pin(server.serve(listener));
+    let client_future = Box::pin(client(endpoint));
+    match futures::future::select(server_future, client_future).await {
+        futures::future::Either::Left((result, _)) => error!("Server stopped: {:?}", result),
+        futures::future::Either::Right((result, _)) => result.unwrap(),
+    };
+}
+
+async fn client(endpoint: String) -> Result<(), rtc::Error> {
+    let peer = Arc::new(rtc::PeerConnection::new(&rtc::Configuration::default()).await?);
+    let data_channel = peer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let (sender, receiver) = futures::channel::oneshot::channel::<Vec<u8>>();
+    let sender = std::sync::Mutex::new(Some(sender));
+    data_channel.on_message(Box::new(move |message, _| {
+        if let Some(sender) = sender.lock().unwrap().take() {
+            _ = sender.send(message);
+        }
+        Box::pin(async {})
+    }));
+    {
+        let data_channel_inner = data_channel.clone();
+        data_channel.on_open(Box::new(move || {
+            let data_channel_inner = data_channel_inner.clone();
+            Box::pin(async move {
+                info!("[client] Sending message...");
+                if let Err(err) = data_channel_inner.send("Hello!".as_bytes()).await {
+                    error!("[client] Failed to send message: {:?}", err);
+                }
+            })
+        }));
+    }
+
+    let offer = peer
+        .create_offer_complete(Some(Duration::from_secs(5)))
+        .await?;
+    let client = rtc::http::HttpSignalingClient::new(endpoint);
+    let (session, answer) = client.send_offer(&offer).await?;
+    info!("[client] Created session {}", session.url());
+    peer.set_remote_description(&answer).await?;
+
+    let message = receiver.await.unwrap();
+    info!(
+        "[client] Received echo: {}",
+        String::from_utf8_lossy(&message)
+    );
+    session.close().await?;
+    peer.close().await?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// A full ICE peer connecting to an ICE-lite peer, as a client would to a public server.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    ice_lite().await.unwrap();
+}
+
+async fn ice_lite() -> Result<(), rtc::Error> {
+    let server = rtc::PeerConnection::new_with_settings(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            ice_lite: true,
+            ..Default::default()
+        },
+    )
+    .await?;
+    let client = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+
+    let (message_sender, mut message_receiver) = mpsc::unbounded::<Vec<u8>>();
+    server.on_data_channel(Box::new(move |data_channel| {
+        let message_sender = message_sender.clone();
+        data_channel.on_message(Box::new(move |message, _| {
+            _ = message_sender.unbounded_send(message);
+            Box::pin(async {})
+        }));
+        Box::pin(async {})
+    }));
+
+    let data_channel = client
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let (open_sender, mut open_receiver) = mpsc::unbounded::<()>();
+    data_channel.on_open(Box::new(move || {
+        _ = open_sender.unbounded_send(());
+        Box::pin(async {})
+    }));
+
+    let timeout = Some(Duration::from_secs(5));
+    let offer = client.create_offer_complete(timeout).await?;
+    server.set_remote_description(&offer).await?;
+    let answer = server.create_answer_complete(timeout).await?;
+
+    // the server advertises ICE-lite and only host candidates
+    let sdp = rtc::sdp::Sdp::from_session_description(&answer)?;
+    assert!(sdp.attribute("ice-lite").is_some());
+    for candidate in sdp.candidates() {
+        assert_eq!(candidate.candidate_type, rtc::CandidateType::Host);
+    }
+
+    client.set_remote_description(&answer).await?;
+    open_receiver.next().await;
+    data_channel.send(b"hello").await?;
+    let message = message_receiver.next().await.unwrap();
+    info!("[server] Received: {}", String::from_utf8_lossy(&message));
+    assert_eq!(message, b"hello");
+
+    client.close().await?;
+    server.close().await?;
+    Ok(())
+}
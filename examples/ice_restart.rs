@@ -0,0 +1,87 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    restart().await.unwrap();
+}
+
+async fn restart() -> Result<(), rtc::Error> {
+    let configuration = rtc::Configuration::default();
+    let peer1 = Arc::new(rtc::PeerConnection::new(&configuration).await?);
+    let peer2 = Arc::new(rtc::PeerConnection::new(&configuration).await?);
+
+    let (message_sender, mut message_receiver) = mpsc::unbounded::<Vec<u8>>();
+    peer2.on_data_channel(Box::new(move |data_channel| {
+        let message_sender = message_sender.clone();
+        data_channel.on_message(Box::new(move |message, _| {
+            _ = message_sender.unbounded_send(message);
+            Box::pin(async {})
+        }));
+        Box::pin(async {})
+    }));
+
+    let data_channel = peer1
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let (open_sender, mut open_receiver) = mpsc::unbounded::<()>();
+    data_channel.on_open(Box::new(move || {
+        _ = open_sender.unbounded_send(());
+        Box::pin(async {})
+    }));
+
+    negotiate(&peer1, &peer2).await?;
+    open_receiver.next().await;
+    data_channel.send(b"before restart").await?;
+    info!(
+        "[peer2] Received: {}",
+        String::from_utf8_lossy(&message_receiver.next().await.unwrap())
+    );
+
+    // the restart is over once ICE is checking the new candidates and connects again
+    let (state_sender, mut state_receiver) = mpsc::unbounded();
+    peer1.on_ice_connection_state_change(Box::new(move |state| {
+        info!("[peer1] ICE connection state: {:?}", state);
+        _ = state_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+
+    info!("[peer1] Restarting ICE");
+    peer1.restart_ice()?;
+    negotiate(&peer1, &peer2).await?;
+    while state_receiver.next().await != Some(rtc::IceConnectionState::Checking) {}
+    while state_receiver.next().await != Some(rtc::IceConnectionState::Connected) {}
+
+    // the existing data channel keeps working over the new ICE session
+    data_channel.send(b"after restart").await?;
+    info!(
+        "[peer2] Received: {}",
+        String::from_utf8_lossy(&message_receiver.next().await.unwrap())
+    );
+
+    peer1.close().await?;
+    peer2.close().await?;
+    Ok(())
+}
+
+/// Candidates are embedded in the descriptions, so none can arrive before the remote
+/// description is set.
+async fn negotiate(
+    offerer: &rtc::PeerConnection,
+    answerer: &rtc::PeerConnection,
+) -> Result<(), rtc::Error> {
+    let offer = offerer
+        .create_offer_complete(Some(Duration::from_secs(5)))
+        .await?;
+    answerer.set_remote_description(&offer).await?;
+    let answer = answerer
+        .create_answer_complete(Some(Duration::from_secs(5)))
+        .await?;
+    offerer.set_remote_description(&answer).await?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+// Accepting peers with an `RtcListener`, using an in-process channel as the signaling source.
+
+use std::{sync::Mutex, time::Duration};
+
+use futures::channel::{mpsc, oneshot};
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+const CLIENTS: usize = 2;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    let (offer_sender, offer_receiver) = mpsc::unbounded::<rtc::IncomingOffer>();
+    let (server, clients) = futures::join!(server(offer_receiver), clients(offer_sender));
+    server.unwrap();
+    clients.unwrap();
+}
+
+async fn server(offers: mpsc::UnboundedReceiver<rtc::IncomingOffer>) -> Result<(), rtc::Error> {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )?;
+    let mut listener = rtc::RtcListener::new(factory, Box::pin(offers))
+        .with_handshake_timeout(Some(Duration::from_secs(10)));
+    let (reply_sender, mut replies) = mpsc::unbounded::<Vec<u8>>();
+    let mut peers = vec![];
+    loop {
+        let accepted = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(rtc::Error::ListenerClosed) => break,
+            Err(err) => return Err(err),
+        };
+        info!(
+            "[server] Accepted peer with {} data channels",
+            accepted.data_channels.len()
+        );
+        for data_channel in accepted
+            .data_channels
+            .iter()
+            .map(|accepted| &accepted.data_channel)
+        {
+            let reply_sender = reply_sender.clone();
+            data_channel.on_message(Box::new(move |message, _| {
+                _ = reply_sender.unbounded_send(message);
+                Box::pin(async {})
+            }));
+            data_channel.send(b"welcome").await?;
+        }
+        peers.push(accepted.peer);
+    }
+    info!("[server] Accepted {} peers", peers.len());
+    // closing right after sending could drop the welcome, so wait for every client to reply
+    for _ in 0..peers.len() {
+        replies.next().await;
+    }
+    for peer in peers {
+        peer.close().await?;
+    }
+    Ok(())
+}
+
+async fn clients(
+    offer_sender: mpsc::UnboundedSender<rtc::IncomingOffer>,
+) -> Result<(), rtc::Error> {
+    let mut clients = vec![];
+    for client in 0..CLIENTS {
+        let peer = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+        let data_channel = peer
+            .create_data_channel(
+                &format!("client {}", client),
+                rtc::DataChannelInit::default(),
+            )
+            .await?;
+        let (message_sender, message_receiver) = mpsc::unbounded::<Vec<u8>>();
+        data_channel.on_message(Box::new(move |message, _| {
+            _ = message_sender.unbounded_send(message);
+            Box::pin(async {})
+        }));
+        let (close_sender, close_receiver) = oneshot::channel::<()>();
+        let close_sender = Mutex::new(Some(close_sender));
+        data_channel.on_close(Box::new(move || {
+            close_sender.lock().unwrap().take();
+            Box::pin(async {})
+        }));
+
+        let offer = peer
+            .create_offer_complete(Some(Duration::from_secs(5)))
+            .await?;
+        let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+        offer_sender.unbounded_send(incoming_offer).unwrap();
+        let answer = answer_receiver
+            .await
+            .map_err(|_| rtc::Error::SignalingClosed)??;
+        peer.set_remote_description(&answer).await?;
+        clients.push((peer, data_channel, message_receiver, close_receiver));
+    }
+    // no more offers, so the listener closes once every handshake is done
+    drop(offer_sender);
+
+    for (client, (_peer, data_channel, message_receiver, _close_receiver)) in
+        clients.iter_mut().enumerate()
+    {
+        let message = message_receiver.next().await.unwrap();
+        info!(
+            "[client {}] Received: {}",
+            client,
+            String::from_utf8_lossy(&message)
+        );
+        data_channel.send(b"thanks").await?;
+    }
+    // the server hangs up once every client has replied
+    for (peer, _data_channel, _message_receiver, close_receiver) in clients {
+        _ = close_receiver.await;
+        peer.close().await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+// Compares the cost of setting up peers with `PeerConnection::new` against a shared
+// `PeerFactory`. Run in release mode for meaningful numbers.
+
+use tracing::{info, Level};
+use unirtc as rtc;
+
+const PEERS: usize = 200;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    benchmark().await.unwrap();
+}
+
+async fn benchmark() -> Result<(), rtc::Error> {
+    let configuration = rtc::Configuration::default();
+
+    let start = now_ms();
+    for _ in 0..PEERS {
+        let peer = rtc::PeerConnection::new(&configuration).await?;
+        set_up(&peer).await?;
+    }
+    let individual = now_ms() - start;
+
+    let start = now_ms();
+    let factory = rtc::PeerFactory::new(&configuration, &rtc::NativeSettings::default())?;
+    for _ in 0..PEERS {
+        let peer = factory.create().await?;
+        set_up(&peer).await?;
+    }
+    let shared = now_ms() - start;
+
+    info!(
+        "PeerConnection::new: {:.0}ms total, {:.2}ms per peer",
+        individual,
+        individual / PEERS as f64
+    );
+    info!(
+        "PeerFactory::create: {:.0}ms total, {:.2}ms per peer",
+        shared,
+        shared / PEERS as f64
+    );
+    Ok(())
+}
+
+async fn set_up(peer: &rtc::PeerConnection) -> Result<(), rtc::Error> {
+    let _data_channel = peer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let offer = peer.create_offer().await?;
+    peer.set_local_description(&offer).await?;
+    peer.close().await?;
+    Ok(())
+}
+
+fn now_ms() -> f64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::{sync::OnceLock, time::Instant};
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+}
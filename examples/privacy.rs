@@ -0,0 +1,73 @@
+// Checks that a peer hiding its IP addresses never signals them.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    privacy().await.unwrap();
+}
+
+async fn privacy() -> Result<(), rtc::Error> {
+    let peer = rtc::PeerConnection::new(&rtc::Configuration {
+        ice_servers: vec![rtc::IceServer::stun("stun.l.google.com", 19302)],
+        ip_privacy: rtc::IpPrivacy::HideLocalAndPublic,
+        ..Default::default()
+    })
+    .await?;
+
+    let trickled = Arc::new(Mutex::new(vec![]));
+    {
+        let trickled = trickled.clone();
+        peer.on_ice_candidate(Box::new(move |ice_candidate| {
+            if let Some(ice_candidate) = ice_candidate {
+                trickled
+                    .lock()
+                    .unwrap()
+                    .push(ice_candidate.to_init().unwrap().candidate);
+            }
+            Box::pin(async {})
+        }));
+    }
+
+    let _data_channel = peer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let offer = peer
+        .create_offer_complete(Some(Duration::from_secs(5)))
+        .await?;
+
+    let mut candidates = rtc::sdp::Sdp::from_session_description(&offer)?.candidates();
+    for candidate in trickled.lock().unwrap().iter() {
+        candidates.push(candidate.parse()?);
+    }
+    for candidate in &candidates {
+        info!("[peer] Signaled: {}", candidate);
+        assert!(!candidate.has_ip_address(), "{}", candidate);
+        assert!(
+            candidate
+                .related_address
+                .as_ref()
+                .is_none_or(|address| address == "0.0.0.0"),
+            "{}",
+            candidate
+        );
+    }
+    for line in offer.sdp().lines().filter(|line| line.starts_with("c=")) {
+        assert!(
+            line == "c=IN IP4 0.0.0.0" || line == "c=IN IP6 ::",
+            "{}",
+            line
+        );
+    }
+    info!("[peer] No IP addresses in {} candidates", candidates.len());
+
+    peer.close().await?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+// A `ReconnectingPeer` reconnecting after the server drops its connection.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    let (offer_sender, offer_receiver) = mpsc::unbounded::<rtc::IncomingOffer>();
+    let (server, client) = futures::join!(server(offer_receiver), client(offer_sender));
+    server.unwrap();
+    client.unwrap();
+}
+
+async fn server(offers: mpsc::UnboundedReceiver<rtc::IncomingOffer>) -> Result<(), rtc::Error> {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )?;
+    let mut listener = rtc::RtcListener::new(factory, Box::pin(offers));
+
+    // the first connection is dropped without a word, as if the server restarted
+    let accepted = listener.accept().await?;
+    info!("[server] Accepted, dropping the connection");
+    accepted.peer.close().await?;
+
+    let accepted = listener.accept().await?;
+    info!("[server] Accepted again");
+    accepted.data_channels[0]
+        .data_channel
+        .send(b"welcome back")
+        .await?;
+
+    // the client closing ends the offer stream
+    match listener.accept().await {
+        Err(rtc::Error::ListenerClosed) => {}
+        Ok(_) => panic!("unexpected peer"),
+        Err(err) => return Err(err),
+    }
+    accepted.peer.close().await?;
+    Ok(())
+}
+
+async fn client(offer_sender: mpsc::UnboundedSender<rtc::IncomingOffer>) -> Result<(), rtc::Error> {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            // notice the dropped connection quickly
+            ice_timeouts: rtc::IceTimeouts {
+                disconnected: Some(Duration::from_secs(2)),
+                failed: Some(Duration::from_secs(2)),
+                keepalive_interval: Some(Duration::from_millis(500)),
+            },
+            ..Default::default()
+        },
+    )?;
+    let send_offer: rtc::SendOfferFn = Box::new(move |offer| {
+        let offer_sender = offer_sender.clone();
+        Box::pin(async move {
+            let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+            offer_sender
+                .unbounded_send(incoming_offer)
+                .map_err(|_| rtc::Error::SignalingClosed)?;
+            answer_receiver
+                .await
+                .map_err(|_| rtc::Error::SignalingClosed)?
+        })
+    });
+    let peer = rtc::ReconnectingPeer::new(factory, send_offer)
+        .with_data_channel("game", rtc::DataChannelInit::default())
+        .with_backoff(rtc::Backoff {
+            initial_delay: Duration::from_millis(100),
+            max_attempts: Some(5),
+            ..Default::default()
+        })
+        .connect()
+        .await?;
+    info!("[client] Connected");
+
+    loop {
+        match peer.next_event().await? {
+            rtc::ReconnectEvent::Disconnected => info!("[client] Disconnected"),
+            rtc::ReconnectEvent::Reconnecting { attempt, delay } => {
+                info!("[client] Reconnect attempt {} in {:?}", attempt, delay);
+            }
+            rtc::ReconnectEvent::ReconnectFailed { attempt, error } => {
+                info!("[client] Reconnect attempt {} failed: {}", attempt, error);
+            }
+            rtc::ReconnectEvent::Reconnected { attempts } => {
+                info!("[client] Reconnected after {} attempts", attempts);
+            }
+            rtc::ReconnectEvent::Message { label, data } => {
+                info!(
+                    "[client] Received on {}: {}",
+                    label,
+                    String::from_utf8_lossy(&data)
+                );
+                assert_eq!(data, b"welcome back");
+                break;
+            }
+        }
+    }
+    // dropping the peer drops the signaling transport, closing the listener
+    peer.close().await?;
+    drop(peer);
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use futures::channel::oneshot;
+use tracing::{error, info, Level};
+use unirtc as rtc;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    renegotiate().await.unwrap();
+}
+
+async fn renegotiate() -> Result<(), rtc::Error> {
+    let configuration = rtc::Configuration::default();
+    let peer1 = Arc::new(rtc::PeerConnection::new(&configuration).await?);
+    let peer2 = Arc::new(rtc::PeerConnection::new(&configuration).await?);
+
+    exchange_ice_candidates("peer1", &peer1, &peer2);
+    exchange_ice_candidates("peer2", &peer2, &peer1);
+
+    // every time peer1 needs (re)negotiation, run an offer/answer round with peer2
+    {
+        let offerer = peer1.clone();
+        let answerer = peer2.clone();
+        peer1.on_negotiation_needed(Box::new(move || {
+            let offerer = offerer.clone();
+            let answerer = answerer.clone();
+            Box::pin(async move {
+                info!("[peer1] Negotiation needed");
+                if let Err(err) = negotiate(&offerer, &answerer).await {
+                    error!("[peer1] Failed to negotiate: {:?}", err);
+                }
+            })
+        }));
+    }
+
+    let (first_sender, first_receiver) = oneshot::channel::<()>();
+    let (second_sender, second_receiver) = oneshot::channel::<()>();
+    let senders = Mutex::new(vec![second_sender, first_sender]);
+    let received = AtomicUsize::new(0);
+    peer2.on_data_channel(Box::new(move |_data_channel| {
+        let count = received.fetch_add(1, Ordering::Relaxed) + 1;
+        info!("[peer2] Received data channel #{}", count);
+        if let Some(sender) = senders.lock().unwrap().pop() {
+            _ = sender.send(());
+        }
+        Box::pin(async {})
+    }));
+
+    let _initial = peer1
+        .create_data_channel("initial", rtc::DataChannelInit::default())
+        .await?;
+    first_receiver.await.unwrap();
+
+    info!("[peer1] Connection established, opening another data channel");
+    let _late = peer1
+        .create_data_channel("late", rtc::DataChannelInit::default())
+        .await?;
+    second_receiver.await.unwrap();
+    info!("[peer2] Received the data channel created mid-session");
+
+    peer1.close().await?;
+    peer2.close().await?;
+    Ok(())
+}
+
+async fn negotiate(
+    offerer: &rtc::PeerConnection,
+    answerer: &rtc::PeerConnection,
+) -> Result<(), rtc::Error> {
+    let offer = offerer.create_offer().await?;
+    offerer.set_local_description(&offer).await?;
+    answerer.set_remote_description(&offer).await?;
+    let answer = answerer.create_answer().await?;
+    answerer.set_local_description(&answer).await?;
+    offerer.set_remote_description(&answer).await?;
+    Ok(())
+}
+
+fn exchange_ice_candidates(
+    peer_name: &'static str,
+    peer: &Arc<rtc::PeerConnection>,
+    other_peer: &Arc<rtc::PeerConnection>,
+) {
+    let other_peer = other_peer.clone();
+    peer.on_ice_candidate(Box::new(move |ice_candidate| {
+        let other_peer = other_peer.clone();
+        Box::pin(async move {
+            match ice_candidate
+                .map(|ice_candidate| ice_candidate.to_init())
+                .transpose()
+            {
+                Ok(ice_candidate_init) => {
+                    if let Err(err) = other_peer.add_ice_candidate(ice_candidate_init).await {
+                        error!("[{}] Failed to add ice candidate: {:?}", peer_name, err);
+                    }
+                }
+                Err(err) => {
+                    error!("[{}] Failed to parse ice candidate: {:?}", peer_name, err);
+                }
+            }
+        })
+    }));
+}
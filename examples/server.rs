@@ -0,0 +1,106 @@
+// A `Server` relaying chat messages between clients, using an in-process channel for signaling.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{info, Level};
+use unirtc as rtc;
+
+const CLIENTS: usize = 3;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    let (offer_sender, offer_receiver) = mpsc::unbounded::<rtc::IncomingOffer>();
+    let (server, clients) = futures::join!(server(offer_receiver), clients(offer_sender));
+    server.unwrap();
+    clients.unwrap();
+}
+
+async fn server(offers: mpsc::UnboundedReceiver<rtc::IncomingOffer>) -> Result<(), rtc::Error> {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )?;
+    let server = rtc::Server::new(rtc::RtcListener::new(factory, Box::pin(offers)));
+    loop {
+        match server.next_event().await {
+            Ok(rtc::ServerEvent::Connected(client_id)) => {
+                info!("[server] Client {} connected", client_id);
+                server
+                    .send(client_id, format!("welcome {}", client_id).as_bytes())
+                    .await?;
+            }
+            Ok(rtc::ServerEvent::Message { client_id, data }) => {
+                info!(
+                    "[server] Client {} says: {}",
+                    client_id,
+                    String::from_utf8_lossy(&data)
+                );
+                // relay to everyone else
+                server.broadcast_except(&[client_id], &data).await;
+            }
+            Ok(rtc::ServerEvent::Disconnected(client_id)) => {
+                info!("[server] Client {} disconnected", client_id);
+            }
+            Err(rtc::Error::ListenerClosed) => break,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+async fn clients(
+    offer_sender: mpsc::UnboundedSender<rtc::IncomingOffer>,
+) -> Result<(), rtc::Error> {
+    let mut clients = vec![];
+    for client in 0..CLIENTS {
+        let peer = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+        let data_channel = peer
+            .create_data_channel("game", rtc::DataChannelInit::default())
+            .await?;
+        let (message_sender, mut message_receiver) = mpsc::unbounded::<Vec<u8>>();
+        data_channel.on_message(Box::new(move |message, _| {
+            _ = message_sender.unbounded_send(message);
+            Box::pin(async {})
+        }));
+
+        let offer = peer
+            .create_offer_complete(Some(Duration::from_secs(5)))
+            .await?;
+        let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+        offer_sender.unbounded_send(incoming_offer).unwrap();
+        let answer = answer_receiver
+            .await
+            .map_err(|_| rtc::Error::SignalingClosed)??;
+        peer.set_remote_description(&answer).await?;
+
+        let welcome = message_receiver.next().await.unwrap();
+        info!(
+            "[client {}] Received: {}",
+            client,
+            String::from_utf8_lossy(&welcome)
+        );
+        clients.push((peer, data_channel, message_receiver));
+    }
+    drop(offer_sender);
+
+    let (_, data_channel, _) = &clients[0];
+    data_channel.send(b"hello everyone").await?;
+    for (client, (_, _, message_receiver)) in clients.iter_mut().enumerate().skip(1) {
+        let message = message_receiver.next().await.unwrap();
+        info!(
+            "[client {}] Received: {}",
+            client,
+            String::from_utf8_lossy(&message)
+        );
+        assert_eq!(message, b"hello everyone");
+    }
+
+    // the server stops once every client has left
+    for (peer, _, _) in clients {
+        peer.close().await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+// A server accepting several peers through one shared UDP socket.
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use tracing::{error, info, Level};
+use unirtc as rtc;
+
+const CLIENTS: usize = 3;
+
+#[tokasm::main]
+async fn main() {
+    unilog::init(Level::INFO, "webrtc_ice::agent::agent_internal=off,webrtc_ice::agent::agent_gather=off,webrtc::peer_connection=off,webrtc_ice::mdns=off,webrtc_mdns::conn=off");
+    udp_mux().await.unwrap();
+}
+
+async fn udp_mux() -> Result<(), rtc::Error> {
+    let udp_mux = rtc::UdpMux::bind("0.0.0.0:0".parse().unwrap()).await?;
+    let port = udp_mux.local_address().unwrap().port();
+    info!("[server] Listening on UDP port {}", port);
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            udp_mux: Some(udp_mux.clone()),
+            ..Default::default()
+        },
+    )?;
+
+    let (message_sender, mut message_receiver) = mpsc::unbounded::<String>();
+    let mut peers = vec![];
+    for client in 0..CLIENTS {
+        let server_peer = factory.create().await?;
+        let message_sender = message_sender.clone();
+        server_peer.on_data_channel(Box::new(move |data_channel| {
+            let message_sender = message_sender.clone();
+            data_channel.on_message(Box::new(move |message, _| {
+                _ = message_sender.unbounded_send(String::from_utf8_lossy(&message).into_owned());
+                Box::pin(async {})
+            }));
+            Box::pin(async {})
+        }));
+
+        let client_peer = rtc::PeerConnection::new(&rtc::Configuration::default()).await?;
+        let data_channel = client_peer
+            .create_data_channel("data", rtc::DataChannelInit::default())
+            .await?;
+        data_channel.on_open(Box::new({
+            let data_channel = data_channel.clone();
+            move || {
+                let data_channel = data_channel.clone();
+                Box::pin(async move {
+                    let message = format!("hello from client {}", client);
+                    if let Err(err) = data_channel.send(message.as_bytes()).await {
+                        error!("[client {}] Failed to send: {:?}", client, err);
+                    }
+                })
+            }
+        }));
+
+        let timeout = Some(Duration::from_secs(5));
+        let offer = client_peer.create_offer_complete(timeout).await?;
+        server_peer.set_remote_description(&offer).await?;
+        let answer = server_peer.create_answer_complete(timeout).await?;
+        client_peer.set_remote_description(&answer).await?;
+
+        // every server candidate uses the shared port
+        let candidates = rtc::sdp::Sdp::from_session_description(&answer)?.candidates();
+        assert!(!candidates.is_empty());
+        for candidate in candidates {
+            assert_eq!(candidate.port, port, "{}", candidate);
+        }
+
+        peers.push((server_peer, client_peer, data_channel));
+    }
+
+    for _ in 0..CLIENTS {
+        info!(
+            "[server] Received: {}",
+            message_receiver.next().await.unwrap()
+        );
+    }
+
+    for (server_peer, client_peer, _) in peers {
+        client_peer.close().await?;
+        server_peer.close().await?;
+    }
+    udp_mux.close().await?;
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+use futures::{channel::mpsc, lock::Mutex as AsyncMutex, StreamExt};
+
+use crate::{runtime, DataChannel, DataChannelInit};
+
+const REQUEST: u8 = 0;
+const RESPONSE: u8 = 1;
+
+/// The remote peer's clock relative to this one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockEstimate {
+    /// Remote time minus local time, in microseconds.
+    pub offset_micros: i64,
+    /// Half the round-trip time of the fastest exchange, which bounds the error of its offset.
+    pub uncertainty: Duration,
+    /// Exchanges the estimate is based on.
+    pub samples: usize,
+}
+
+enum Packet {
+    /// The remote's send time, and the local receive time.
+    Request { t0: i64, t1: i64 },
+    /// The local send time, remote receive and send times, and the local receive time.
+    Response { t0: i64, t1: i64, t2: i64, t3: i64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    offset: i64,
+    delay: i64,
+}
+
+impl Sample {
+    /// `None` if the remote times are too far off to compute with, or claim more time was
+    /// spent remotely than the exchange took, as only a broken or forged response does.
+    fn new(t0: i64, t1: i64, t2: i64, t3: i64) -> Option<Self> {
+        let offset = t1.checked_sub(t0)?.checked_add(t2.checked_sub(t3)?)? / 2;
+        let delay = t3.checked_sub(t0)?.checked_sub(t2.checked_sub(t1)?)?;
+        (delay >= 0).then_some(Sample { offset, delay })
+    }
+}
+
+/// Estimates the offset between this peer's clock and the remote peer's, for a time base
+/// shared by both.
+///
+/// Works like NTP: each exchange records when a request was sent, received, answered and the
+/// answer received, giving an offset and a round-trip delay. Exchanges delayed by queueing
+/// skew the offset, so only the faster half of recent exchanges are used, and the median of
+/// their offsets is taken.
+///
+/// Both peers run a clock sync on the same channel, set up like a [`Heartbeat`]'s with
+/// [`ClockSync::LABEL`]. Times are [`ClockSync::local_now`], which is monotonic and unrelated
+/// to the wall clock. Exchanges are only made while [`ClockSync::run`] is awaited.
+///
+/// [`Heartbeat`]: crate::Heartbeat
+pub struct ClockSync {
+    data_channel: DataChannel,
+    interval: Duration,
+    window: usize,
+    packets: AsyncMutex<mpsc::UnboundedReceiver<Packet>>,
+    /// Closing the packet channel ends [`ClockSync::run`].
+    stop_sender: mpsc::UnboundedSender<Packet>,
+    samples: Mutex<VecDeque<Sample>>,
+    estimate: Mutex<Option<ClockEstimate>>,
+}
+
+impl ClockSync {
+    pub const LABEL: &'static str = "clock-sync";
+
+    /// Unordered and unreliable, so a lost exchange never delays the next one.
+    pub fn data_channel_init() -> DataChannelInit {
+        DataChannelInit {
+            ordered: Some(false),
+            max_retransmits: Some(0),
+        }
+    }
+
+    /// Takes over the `on_message` and `on_close` handlers of `data_channel`.
+    pub fn new(data_channel: DataChannel) -> Self {
+        let (packet_sender, packets) = mpsc::unbounded();
+        let stop_sender = packet_sender.clone();
+        let close_sender = packet_sender.clone();
+        data_channel.on_close(Box::new(move || {
+            // ends `run` once the packets already received are handled
+            close_sender.close_channel();
+            Box::pin(async {})
+        }));
+        data_channel.on_message(Box::new(move |message, _| {
+            let received = micros(runtime::now());
+            let packet = match message.as_slice() {
+                [REQUEST, t0 @ ..] if t0.len() == 8 => Some(Packet::Request {
+                    t0: read(t0, 0),
+                    t1: received,
+                }),
+                [RESPONSE, times @ ..] if times.len() == 24 => Some(Packet::Response {
+                    t0: read(times, 0),
+                    t1: read(times, 1),
+                    t2: read(times, 2),
+                    t3: received,
+                }),
+                _ => None,
+            };
+            if let Some(packet) = packet {
+                _ = packet_sender.unbounded_send(packet);
+            }
+            Box::pin(async {})
+        }));
+        ClockSync {
+            data_channel,
+            interval: Duration::from_secs(1),
+            window: 16,
+            packets: AsyncMutex::new(packets),
+            stop_sender,
+            samples: Mutex::new(VecDeque::new()),
+            estimate: Mutex::new(None),
+        }
+    }
+
+    /// How often to start an exchange, every second by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// How many recent exchanges to estimate from, 16 by default. Larger windows reject more
+    /// outliers, but are slower to follow clock drift.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// The time this peer's estimates are relative to.
+    pub fn local_now() -> Duration {
+        runtime::now()
+    }
+
+    /// The latest estimate, once an exchange has completed.
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        *self.estimate.lock().unwrap()
+    }
+
+    /// The remote peer's [`ClockSync::local_now`] at this moment.
+    pub fn remote_now(&self) -> Option<Duration> {
+        self.to_remote(Self::local_now())
+    }
+
+    /// Converts a local time to the remote peer's clock.
+    pub fn to_remote(&self, local: Duration) -> Option<Duration> {
+        let offset = self.estimate()?.offset_micros;
+        Some(from_micros(micros(local).saturating_add(offset)))
+    }
+
+    /// Converts a time on the remote peer's clock to the local one.
+    pub fn to_local(&self, remote: Duration) -> Option<Duration> {
+        let offset = self.estimate()?.offset_micros;
+        Some(from_micros(micros(remote).saturating_sub(offset)))
+    }
+
+    /// Makes exchanges with the remote peer and answers its requests. Returns once
+    /// [`ClockSync::stop`] is called or the data channel closes. The native implementation only
+    /// reports channels closed by the remote peer, so call [`ClockSync::stop`] when closing
+    /// this one.
+    pub async fn run(&self) {
+        let mut packets = self.packets.lock().await;
+        // send times of the requests not answered yet, as responses are only trusted for those
+        let mut outstanding = VecDeque::new();
+        let mut next_request = runtime::now();
+        loop {
+            let now = runtime::now();
+            if now >= next_request {
+                // lost requests are simply never answered
+                let t0 = micros(now);
+                _ = self.data_channel.send(&request(t0)).await;
+                outstanding.push_back(t0);
+                while outstanding.len() > self.window {
+                    outstanding.pop_front();
+                }
+                next_request = now + self.interval;
+            }
+            let wait = next_request.saturating_sub(now);
+            let Some(received) = runtime::timeout(Some(wait), packets.next()).await else {
+                continue;
+            };
+            match received {
+                Some(Packet::Request { t0, t1 }) => {
+                    let t2 = micros(runtime::now());
+                    _ = self.data_channel.send(&response(t0, t1, t2)).await;
+                }
+                Some(Packet::Response { t0, t1, t2, t3 }) => {
+                    let Some(position) = outstanding.iter().position(|&sent| sent == t0) else {
+                        continue;
+                    };
+                    outstanding.remove(position);
+                    if let Some(sample) = Sample::new(t0, t1, t2, t3) {
+                        self.record(sample);
+                    }
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Ends [`ClockSync::run`], or makes later calls return at once. The estimate is kept.
+    pub fn stop(&self) {
+        self.stop_sender.close_channel();
+    }
+
+    fn record(&self, sample: Sample) {
+        let mut samples = self.samples.lock().unwrap();
+        samples.push_back(sample);
+        while samples.len() > self.window {
+            samples.pop_front();
+        }
+        let mut fastest: Vec<Sample> = samples.iter().copied().collect();
+        fastest.sort_by_key(|sample| sample.delay);
+        fastest.truncate(fastest.len().div_ceil(2));
+        let uncertainty = from_micros(fastest[0].delay / 2);
+        let mut offsets: Vec<i64> = fastest.iter().map(|sample| sample.offset).collect();
+        offsets.sort_unstable();
+        *self.estimate.lock().unwrap() = Some(ClockEstimate {
+            offset_micros: offsets[offsets.len() / 2],
+            uncertainty,
+            samples: samples.len(),
+        });
+    }
+}
+
+impl std::fmt::Debug for ClockSync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClockSync")
+            .field("interval", &self.interval)
+            .field("window", &self.window)
+            .field("estimate", &self.estimate())
+            .finish_non_exhaustive()
+    }
+}
+
+fn micros(time: Duration) -> i64 {
+    time.as_micros().min(i64::MAX as u128) as i64
+}
+
+fn from_micros(micros: i64) -> Duration {
+    Duration::from_micros(micros.max(0) as u64)
+}
+
+fn read(bytes: &[u8], index: usize) -> i64 {
+    let mut time = [0; 8];
+    time.copy_from_slice(&bytes[index * 8..index * 8 + 8]);
+    i64::from_le_bytes(time)
+}
+
+fn request(t0: i64) -> [u8; 9] {
+    let mut packet = [REQUEST; 9];
+    packet[1..].copy_from_slice(&t0.to_le_bytes());
+    packet
+}
+
+fn response(t0: i64, t1: i64, t2: i64) -> [u8; 25] {
+    let mut packet = [RESPONSE; 25];
+    for (index, time) in [t0, t1, t2].into_iter().enumerate() {
+        packet[1 + index * 8..9 + index * 8].copy_from_slice(&time.to_le_bytes());
+    }
+    packet
+}
@@ -0,0 +1,599 @@
+//! Compact encoding of data-channel-only session descriptions.
+//!
+//! A complete SDP is several kilobytes, which is unwieldy to paste between machines or to fit
+//! in a QR code. [`encode`] keeps only what a data-channel-only session actually negotiates
+//! (ICE credentials, DTLS fingerprint and role, and UDP candidates) and packs it into a short
+//! base64url string. [`decode`] rebuilds a valid SDP from it on the other side.
+//!
+//! Descriptions should be created with ICE gathering complete (see
+//! [`PeerConnection::create_offer_complete`](crate::PeerConnection::create_offer_complete)),
+//! since trickled candidates are not part of the encoding.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{sdp::Sdp, CandidateType, Error, SdpType, SessionDescription};
+
+const VERSION: u8 = 2;
+const SCTP_PORT: u16 = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DtlsSetup {
+    ActPass,
+    Active,
+    Passive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CandidateAddress {
+    Ip(IpAddr),
+    /// An mDNS (`.local`) or other hostname, as browsers use to hide host addresses.
+    Hostname(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactCandidate {
+    pub address: CandidateAddress,
+    pub port: u16,
+    pub candidate_type: CandidateType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactDescription {
+    pub sdp_type: SdpType,
+    pub mid: String,
+    pub ice_ufrag: String,
+    pub ice_pwd: String,
+    /// SHA-256 DTLS certificate fingerprint.
+    pub fingerprint: [u8; 32],
+    pub setup: DtlsSetup,
+    pub max_message_size: Option<u32>,
+    pub candidates: Vec<CompactCandidate>,
+}
+
+/// Encodes an offer or answer into a compact base64url string.
+pub fn encode(session_description: &SessionDescription) -> Result<String, Error> {
+    let compact = CompactDescription::from_session_description(session_description)?;
+    Ok(URL_SAFE_NO_PAD.encode(compact.to_bytes()?))
+}
+
+/// Decodes a string produced by [`encode`] back into a session description.
+pub fn decode(encoded: &str) -> Result<SessionDescription, Error> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(encoded.trim())
+        .map_err(|_| Error::FailedToDecodeSessionDescription)?;
+    CompactDescription::from_bytes(&bytes)?.to_session_description()
+}
+
+impl CompactDescription {
+    pub fn from_session_description(
+        session_description: &SessionDescription,
+    ) -> Result<Self, Error> {
+        Self::from_sdp(session_description.sdp_type(), &session_description.sdp())
+    }
+
+    pub fn from_sdp(sdp_type: SdpType, sdp: &str) -> Result<Self, Error> {
+        if !matches!(sdp_type, SdpType::Offer | SdpType::Answer) {
+            return Err(Error::UnexpectedSdpType);
+        }
+        let sdp = Sdp::parse(sdp).map_err(|_| Error::FailedToEncodeSessionDescription)?;
+        let [media] = sdp.media.as_slice() else {
+            return Err(Error::FailedToEncodeSessionDescription);
+        };
+        if media.description.media != "application" {
+            return Err(Error::FailedToEncodeSessionDescription);
+        }
+        let fingerprint = sdp
+            .fingerprint()
+            .filter(|fingerprint| fingerprint.algorithm == "sha-256")
+            .and_then(|fingerprint| <[u8; 32]>::try_from(fingerprint.value).ok())
+            .ok_or(Error::FailedToEncodeSessionDescription)?;
+        let setup = match media.attribute("setup").or_else(|| sdp.attribute("setup")) {
+            None | Some("actpass") => DtlsSetup::ActPass,
+            Some("active") => DtlsSetup::Active,
+            Some("passive") => DtlsSetup::Passive,
+            Some(_) => return Err(Error::FailedToEncodeSessionDescription),
+        };
+        let candidates = media
+            .candidates()
+            .into_iter()
+            // only UDP candidates for the RTP component are kept
+            .filter(|candidate| {
+                candidate.component == 1 && candidate.protocol.eq_ignore_ascii_case("udp")
+            })
+            .map(|candidate| CompactCandidate {
+                address: match candidate.address.parse::<IpAddr>() {
+                    Ok(ip) => CandidateAddress::Ip(ip),
+                    Err(_) => CandidateAddress::Hostname(candidate.address),
+                },
+                port: candidate.port,
+                candidate_type: candidate.candidate_type,
+            })
+            .collect();
+        match (media.mid(), sdp.ice_ufrag(), sdp.ice_pwd()) {
+            (Some(mid), Some(ice_ufrag), Some(ice_pwd)) => Ok(Self {
+                sdp_type,
+                mid: mid.to_owned(),
+                ice_ufrag: ice_ufrag.to_owned(),
+                ice_pwd: ice_pwd.to_owned(),
+                fingerprint,
+                setup,
+                max_message_size: media.max_message_size(),
+                candidates,
+            }),
+            _ => Err(Error::FailedToEncodeSessionDescription),
+        }
+    }
+
+    /// Fails if the mid, ICE credentials or a candidate hostname contain whitespace or line
+    /// breaks, which would change the meaning of the SDP.
+    pub fn to_sdp(&self) -> Result<String, Error> {
+        let hostnames = self
+            .candidates
+            .iter()
+            .filter_map(|candidate| match &candidate.address {
+                CandidateAddress::Hostname(hostname) => Some(hostname.as_str()),
+                CandidateAddress::Ip(_) => None,
+            });
+        if ![&self.mid, &self.ice_ufrag, &self.ice_pwd]
+            .into_iter()
+            .map(String::as_str)
+            .chain(hostnames)
+            .all(is_sdp_token)
+        {
+            return Err(Error::FailedToDecodeSessionDescription);
+        }
+        if !matches!(self.sdp_type, SdpType::Offer | SdpType::Answer) {
+            return Err(Error::UnexpectedSdpType);
+        }
+        let mut lines = vec![];
+        let session_id = u64::from_be_bytes(self.fingerprint[..8].try_into().unwrap()) >> 1;
+        lines.push("v=0".to_owned());
+        lines.push(format!("o=- {} 2 IN IP4 127.0.0.1", session_id));
+        lines.push("s=-".to_owned());
+        lines.push("t=0 0".to_owned());
+        lines.push(format!("a=group:BUNDLE {}", self.mid));
+        lines.push("m=application 9 UDP/DTLS/SCTP webrtc-datachannel".to_owned());
+        lines.push("c=IN IP4 0.0.0.0".to_owned());
+        lines.push(format!("a=ice-ufrag:{}", self.ice_ufrag));
+        lines.push(format!("a=ice-pwd:{}", self.ice_pwd));
+        lines.push(format!(
+            "a=fingerprint:sha-256 {}",
+            self.fingerprint
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<_>>()
+                .join(":")
+        ));
+        lines.push(format!(
+            "a=setup:{}",
+            match self.setup {
+                DtlsSetup::ActPass => "actpass",
+                DtlsSetup::Active => "active",
+                DtlsSetup::Passive => "passive",
+            }
+        ));
+        lines.push(format!("a=mid:{}", self.mid));
+        lines.push(format!("a=sctp-port:{}", SCTP_PORT));
+        if let Some(max_message_size) = self.max_message_size {
+            lines.push(format!("a=max-message-size:{}", max_message_size));
+        }
+        for (index, candidate) in self.candidates.iter().enumerate() {
+            let (type_name, type_preference) = match candidate.candidate_type {
+                CandidateType::Host | CandidateType::Unspecified => ("host", 126),
+                CandidateType::PeerReflexive => ("prflx", 110),
+                CandidateType::ServerReflexive => ("srflx", 100),
+                CandidateType::Relay => ("relay", 0),
+            };
+            let local_preference = 65535 - index.min(65535) as u32;
+            let priority = (type_preference << 24) | (local_preference << 8) | 255;
+            let address = match &candidate.address {
+                CandidateAddress::Ip(ip) => ip.to_string(),
+                CandidateAddress::Hostname(hostname) => hostname.clone(),
+            };
+            let mut line = format!(
+                "a=candidate:{} 1 udp {} {} {} typ {}",
+                index + 1,
+                priority,
+                address,
+                candidate.port,
+                type_name
+            );
+            if type_name != "host" {
+                line.push_str(" raddr 0.0.0.0 rport 0");
+            }
+            lines.push(line);
+        }
+        lines.push("a=end-of-candidates".to_owned());
+        lines.push(String::new());
+        Ok(lines.join("\r\n"))
+    }
+
+    pub fn to_session_description(&self) -> Result<SessionDescription, Error> {
+        SessionDescription::new(self.sdp_type, &self.to_sdp()?)
+    }
+
+    /// Fails if a string is longer than 255 bytes or there are more than 255 candidates, and
+    /// with [`Error::UnexpectedSdpType`] for types other than offers and answers.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        let sdp_type: u8 = match self.sdp_type {
+            SdpType::Offer => 0,
+            SdpType::Answer => 1,
+            _ => return Err(Error::UnexpectedSdpType),
+        };
+        let setup: u8 = match self.setup {
+            DtlsSetup::ActPass => 0,
+            DtlsSetup::Active => 1,
+            DtlsSetup::Passive => 2,
+        };
+        bytes.push((VERSION << 4) | (sdp_type << 2) | setup);
+        write_string(&mut bytes, &self.mid)?;
+        write_string(&mut bytes, &self.ice_ufrag)?;
+        write_string(&mut bytes, &self.ice_pwd)?;
+        bytes.extend_from_slice(&self.fingerprint);
+        match self.max_message_size {
+            Some(max_message_size) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&max_message_size.to_be_bytes());
+            }
+            None => bytes.push(0),
+        }
+        bytes.push(
+            u8::try_from(self.candidates.len())
+                .map_err(|_| Error::FailedToEncodeSessionDescription)?,
+        );
+        for candidate in &self.candidates {
+            let candidate_type: u8 = match candidate.candidate_type {
+                CandidateType::Host | CandidateType::Unspecified => 0,
+                CandidateType::ServerReflexive => 1,
+                CandidateType::PeerReflexive => 2,
+                CandidateType::Relay => 3,
+            };
+            match &candidate.address {
+                CandidateAddress::Ip(IpAddr::V4(ip)) => {
+                    bytes.push(candidate_type << 2);
+                    bytes.extend_from_slice(&ip.octets());
+                }
+                CandidateAddress::Ip(IpAddr::V6(ip)) => {
+                    bytes.push((candidate_type << 2) | 1);
+                    bytes.extend_from_slice(&ip.octets());
+                }
+                CandidateAddress::Hostname(hostname) => {
+                    if let Some(uuid) = parse_mdns_uuid(hostname) {
+                        bytes.push((candidate_type << 2) | 2);
+                        bytes.extend_from_slice(&uuid);
+                    } else {
+                        bytes.push((candidate_type << 2) | 3);
+                        write_string(&mut bytes, hostname)?;
+                    }
+                }
+            }
+            bytes.extend_from_slice(&candidate.port.to_be_bytes());
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader(bytes);
+        let header = reader.u8()?;
+        if header >> 4 != VERSION {
+            return Err(Error::FailedToDecodeSessionDescription);
+        }
+        let sdp_type = match (header >> 2) & 0b11 {
+            0 => SdpType::Offer,
+            1 => SdpType::Answer,
+            _ => return Err(Error::FailedToDecodeSessionDescription),
+        };
+        let setup = match header & 0b11 {
+            0 => DtlsSetup::ActPass,
+            1 => DtlsSetup::Active,
+            2 => DtlsSetup::Passive,
+            _ => return Err(Error::FailedToDecodeSessionDescription),
+        };
+        let mid = reader.token()?;
+        let ice_ufrag = reader.token()?;
+        let ice_pwd = reader.token()?;
+        let fingerprint = reader.array::<32>()?;
+        let max_message_size = match reader.u8()? {
+            0 => None,
+            1 => Some(u32::from_be_bytes(reader.array()?)),
+            _ => return Err(Error::FailedToDecodeSessionDescription),
+        };
+        let candidate_count = reader.u8()?;
+        let mut candidates = Vec::with_capacity(candidate_count as usize);
+        for _ in 0..candidate_count {
+            let flags = reader.u8()?;
+            let candidate_type = match flags >> 2 {
+                0 => CandidateType::Host,
+                1 => CandidateType::ServerReflexive,
+                2 => CandidateType::PeerReflexive,
+                3 => CandidateType::Relay,
+                _ => return Err(Error::FailedToDecodeSessionDescription),
+            };
+            let address = match flags & 0b11 {
+                0 => CandidateAddress::Ip(IpAddr::V4(Ipv4Addr::from(reader.array::<4>()?))),
+                1 => CandidateAddress::Ip(IpAddr::V6(Ipv6Addr::from(reader.array::<16>()?))),
+                2 => CandidateAddress::Hostname(format_mdns_uuid(&reader.array::<16>()?)),
+                _ => CandidateAddress::Hostname(reader.token()?),
+            };
+            let port = u16::from_be_bytes(reader.array()?);
+            candidates.push(CompactCandidate {
+                address,
+                port,
+                candidate_type,
+            });
+        }
+        if !reader.0.is_empty() {
+            return Err(Error::FailedToDecodeSessionDescription);
+        }
+        Ok(Self {
+            sdp_type,
+            mid,
+            ice_ufrag,
+            ice_pwd,
+            fingerprint,
+            setup,
+            max_message_size,
+            candidates,
+        })
+    }
+}
+
+fn parse_mdns_uuid(hostname: &str) -> Option<[u8; 16]> {
+    let hex = hostname.strip_suffix(".local")?.replace('-', "");
+    if hex.len() != 32 {
+        return None;
+    }
+    let mut uuid = [0; 16];
+    for (index, byte) in uuid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(index * 2..index * 2 + 2)?, 16).ok()?;
+    }
+    // only pack names that will be reproduced exactly
+    (format_mdns_uuid(&uuid) == hostname).then_some(uuid)
+}
+
+fn format_mdns_uuid(uuid: &[u8; 16]) -> String {
+    let hex = uuid
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}.local",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Whether `string` fits in an SDP field without splitting it or ending the line.
+fn is_sdp_token(string: &str) -> bool {
+    !string
+        .chars()
+        .any(|char| char.is_whitespace() || char.is_control())
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) -> Result<(), Error> {
+    let len = u8::try_from(string.len()).map_err(|_| Error::FailedToEncodeSessionDescription)?;
+    bytes.push(len);
+    bytes.extend_from_slice(string.as_bytes());
+    Ok(())
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < len {
+            return Err(Error::FailedToDecodeSessionDescription);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    /// A string that can be placed in an SDP line as is.
+    fn token(&mut self) -> Result<String, Error> {
+        let len = self.u8()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .ok()
+            .filter(|string| is_sdp_token(string))
+            .ok_or(Error::FailedToDecodeSessionDescription)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn description() -> CompactDescription {
+        CompactDescription {
+            sdp_type: SdpType::Offer,
+            mid: "0".to_owned(),
+            ice_ufrag: "EsAw".to_owned(),
+            ice_pwd: "P2uYro0UCOQ4zxjKXaWCBui1".to_owned(),
+            fingerprint: std::array::from_fn(|index| index as u8 * 7),
+            setup: DtlsSetup::ActPass,
+            max_message_size: Some(262144),
+            candidates: vec![
+                CompactCandidate {
+                    address: CandidateAddress::Ip("192.168.1.20".parse().unwrap()),
+                    port: 50000,
+                    candidate_type: CandidateType::Host,
+                },
+                CompactCandidate {
+                    address: CandidateAddress::Ip("2001:db8::1".parse().unwrap()),
+                    port: 50001,
+                    candidate_type: CandidateType::ServerReflexive,
+                },
+                CompactCandidate {
+                    address: CandidateAddress::Hostname(
+                        "4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local".to_owned(),
+                    ),
+                    port: 50002,
+                    candidate_type: CandidateType::Host,
+                },
+                CompactCandidate {
+                    address: CandidateAddress::Hostname("turn.example.com".to_owned()),
+                    port: 3478,
+                    candidate_type: CandidateType::Relay,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let description = description();
+        let bytes = description.to_bytes().unwrap();
+        assert_eq!(CompactDescription::from_bytes(&bytes).unwrap(), description);
+    }
+
+    #[test]
+    fn sdp_round_trip() {
+        let description = description();
+        let sdp = description.to_sdp().unwrap();
+        assert_eq!(
+            CompactDescription::from_sdp(SdpType::Offer, &sdp).unwrap(),
+            description
+        );
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let session_description = description().to_session_description().unwrap();
+        let decoded = decode(&encode(&session_description).unwrap()).unwrap();
+        assert_eq!(decoded.sdp_type(), SdpType::Offer);
+        assert_eq!(decoded.sdp(), session_description.sdp());
+    }
+
+    #[test]
+    fn max_message_size_zero_is_not_none() {
+        for max_message_size in [None, Some(0), Some(u32::MAX)] {
+            let description = CompactDescription {
+                max_message_size,
+                ..description()
+            };
+            let bytes = description.to_bytes().unwrap();
+            assert_eq!(
+                CompactDescription::from_bytes(&bytes)
+                    .unwrap()
+                    .max_message_size,
+                max_message_size
+            );
+        }
+    }
+
+    #[test]
+    fn long_strings_are_rejected() {
+        // 254 bytes of two-byte characters fit, 256 do not
+        let description = CompactDescription {
+            ice_pwd: "é".repeat(127),
+            ..description()
+        };
+        let bytes = description.to_bytes().unwrap();
+        assert_eq!(CompactDescription::from_bytes(&bytes).unwrap(), description);
+        let description = CompactDescription {
+            ice_pwd: "é".repeat(128),
+            ..description
+        };
+        assert_eq!(
+            description.to_bytes(),
+            Err(Error::FailedToEncodeSessionDescription)
+        );
+    }
+
+    #[test]
+    fn too_many_candidates_are_rejected() {
+        let candidate = description().candidates[0].clone();
+        let description = CompactDescription {
+            candidates: vec![candidate.clone(); 255],
+            ..description()
+        };
+        let bytes = description.to_bytes().unwrap();
+        assert_eq!(CompactDescription::from_bytes(&bytes).unwrap(), description);
+        let description = CompactDescription {
+            candidates: vec![candidate; 256],
+            ..description
+        };
+        assert_eq!(
+            description.to_bytes(),
+            Err(Error::FailedToEncodeSessionDescription)
+        );
+    }
+
+    #[test]
+    fn malformed_bytes_are_rejected() {
+        let bytes = description().to_bytes().unwrap();
+        for bytes in [
+            &bytes[..bytes.len() - 1],
+            &[bytes.as_slice(), &[0]].concat(),
+            &[&[(VERSION + 1) << 4], &bytes[1..]].concat(),
+        ] {
+            assert_eq!(
+                CompactDescription::from_bytes(bytes),
+                Err(Error::FailedToDecodeSessionDescription)
+            );
+        }
+    }
+
+    #[test]
+    fn unsafe_strings_are_rejected() {
+        for unsafe_string in ["0\r\na=setup:active", "0\n", "a b", "a\tb"] {
+            let descriptions = [
+                CompactDescription {
+                    mid: unsafe_string.to_owned(),
+                    ..description()
+                },
+                CompactDescription {
+                    ice_ufrag: unsafe_string.to_owned(),
+                    ..description()
+                },
+                CompactDescription {
+                    ice_pwd: unsafe_string.to_owned(),
+                    ..description()
+                },
+                CompactDescription {
+                    candidates: vec![CompactCandidate {
+                        address: CandidateAddress::Hostname(unsafe_string.to_owned()),
+                        port: 3478,
+                        candidate_type: CandidateType::Relay,
+                    }],
+                    ..description()
+                },
+            ];
+            for description in descriptions {
+                assert_eq!(
+                    description.to_sdp(),
+                    Err(Error::FailedToDecodeSessionDescription)
+                );
+                let bytes = description.to_bytes().unwrap();
+                assert_eq!(
+                    CompactDescription::from_bytes(&bytes),
+                    Err(Error::FailedToDecodeSessionDescription)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn only_offers_and_answers_are_encoded() {
+        let description = CompactDescription {
+            sdp_type: SdpType::Pranswer,
+            ..description()
+        };
+        assert_eq!(description.to_bytes(), Err(Error::UnexpectedSdpType));
+        assert_eq!(description.to_sdp(), Err(Error::UnexpectedSdpType));
+    }
+}
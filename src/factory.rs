@@ -0,0 +1,108 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{atomic::AtomicBool, Arc};
+
+#[cfg(target_arch = "wasm32")]
+use crate::wasm;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{native, MdnsMode};
+use crate::{privacy::Privacy, Configuration, Error, IpPrivacy, NativeSettings, PeerConnection};
+
+/// Creates peers sharing one configuration.
+///
+/// On native, the media engine, interceptors and settings are set up once and shared by every
+/// peer, which makes creating many peers much cheaper than [`PeerConnection::new`]. In the
+/// browser, this only holds on to the configuration.
+#[derive(Clone)]
+pub struct PeerFactory {
+    #[cfg(not(target_arch = "wasm32"))]
+    api: Arc<native::API>,
+    #[cfg(not(target_arch = "wasm32"))]
+    configuration: native::RTCConfiguration,
+    #[cfg(target_arch = "wasm32")]
+    configuration: wasm::RtcConfiguration,
+    ip_privacy: IpPrivacy,
+}
+
+impl PeerFactory {
+    /// `settings` are ignored in the browser.
+    pub fn new(configuration: &Configuration, settings: &NativeSettings) -> Result<Self, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut settings = settings.clone();
+            // host candidates are hidden behind mDNS names rather than dropped altogether
+            if configuration.ip_privacy != IpPrivacy::Disabled
+                && settings.mdns_mode == MdnsMode::QueryOnly
+            {
+                settings.mdns_mode = MdnsMode::QueryAndGather;
+            }
+            Ok(PeerFactory {
+                api: Arc::new(build_api(&settings)?),
+                configuration: native::RTCConfiguration::try_from(configuration.clone())?,
+                ip_privacy: configuration.ip_privacy,
+            })
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            _ = settings;
+            Ok(PeerFactory {
+                configuration: wasm::RtcConfiguration::try_from(configuration.clone())?,
+                ip_privacy: configuration.ip_privacy,
+            })
+        }
+    }
+
+    pub async fn create(&self) -> Result<PeerConnection, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let peer = self
+                .api
+                .new_peer_connection(self.configuration.clone())
+                .await
+                .map_err(|_| Error::FailedToCreatePeer)?;
+            Ok(PeerConnection(
+                peer,
+                Privacy::new(self.ip_privacy),
+                AtomicBool::new(false),
+            ))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Ok(PeerConnection(
+                wasm::RtcPeerConnection::new_with_configuration(&self.configuration)
+                    .map_err(|_| Error::FailedToCreatePeer)?,
+                Privacy::new(self.ip_privacy),
+            ))
+        }
+    }
+}
+
+impl std::fmt::Debug for PeerFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PeerFactory").finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn build_api(settings: &NativeSettings) -> Result<native::API, Error> {
+    use webrtc::{
+        api::{
+            interceptor_registry::register_default_interceptors, media_engine::MediaEngine,
+            APIBuilder,
+        },
+        interceptor::registry::Registry,
+    };
+    let mut media_engine = MediaEngine::default();
+    let mut registry = Registry::new();
+    if !settings.data_channels_only {
+        media_engine
+            .register_default_codecs()
+            .map_err(|_| Error::FailedToCreatePeer)?;
+        registry = register_default_interceptors(registry, &mut media_engine)
+            .map_err(|_| Error::FailedToCreatePeer)?;
+    }
+    Ok(APIBuilder::new()
+        .with_media_engine(media_engine)
+        .with_interceptor_registry(registry)
+        .with_setting_engine(settings.setting_engine()?)
+        .build())
+}
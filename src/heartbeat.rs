@@ -0,0 +1,168 @@
+use std::{sync::Mutex, time::Duration};
+
+use futures::{channel::mpsc, lock::Mutex as AsyncMutex, StreamExt};
+
+use crate::{runtime, DataChannel, DataChannelInit, Error};
+
+const PING: u8 = 0;
+const PONG: u8 = 1;
+/// Shorter intervals would ping in a busy loop.
+const MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Round-trip times measured by a [`Heartbeat`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RttStats {
+    pub latest: Option<Duration>,
+    /// Smoothed like TCP's round-trip time estimate, giving each new sample a weight of 1/8.
+    pub mean: Option<Duration>,
+    /// Smoothed difference between consecutive samples, as for RTP interarrival jitter.
+    pub jitter: Duration,
+    pub min: Option<Duration>,
+    pub samples: u64,
+}
+
+impl RttStats {
+    fn record(&mut self, rtt: Duration) {
+        self.mean = Some(match self.mean {
+            Some(mean) => smooth(mean, rtt, 8.),
+            None => rtt,
+        });
+        if let Some(latest) = self.latest {
+            let difference = rtt.max(latest) - rtt.min(latest);
+            self.jitter = smooth(self.jitter, difference, 16.);
+        }
+        self.min = Some(self.min.map_or(rtt, |min| min.min(rtt)));
+        self.latest = Some(rtt);
+        self.samples += 1;
+    }
+}
+
+fn smooth(average: Duration, sample: Duration, weight: f64) -> Duration {
+    let average = average.as_secs_f64();
+    Duration::from_secs_f64(average + (sample.as_secs_f64() - average) / weight)
+}
+
+/// Pings the remote peer over a dedicated data channel, measuring round-trip times and
+/// noticing a silent peer long before ICE does.
+///
+/// Both peers run a heartbeat on the same channel: one creates it with [`Heartbeat::LABEL`]
+/// and [`Heartbeat::data_channel_init`], and the other receives it from
+/// [`PeerConnection::on_data_channel`](crate::PeerConnection::on_data_channel). Pings are only
+/// sent and answered while [`Heartbeat::run`] is awaited.
+pub struct Heartbeat {
+    data_channel: DataChannel,
+    interval: Duration,
+    timeout: Duration,
+    packets: AsyncMutex<mpsc::UnboundedReceiver<(u8, u64)>>,
+    stats: Mutex<RttStats>,
+}
+
+impl Heartbeat {
+    pub const LABEL: &'static str = "heartbeat";
+
+    /// Unordered and unreliable, so a lost ping never delays the next one.
+    pub fn data_channel_init() -> DataChannelInit {
+        DataChannelInit {
+            ordered: Some(false),
+            max_retransmits: Some(0),
+        }
+    }
+
+    pub fn new(data_channel: DataChannel) -> Self {
+        let (packet_sender, packets) = mpsc::unbounded();
+        data_channel.on_message(Box::new(move |message, _| {
+            if let [kind @ (PING | PONG), time @ ..] = message.as_slice() {
+                if let Ok(time) = time.try_into() {
+                    _ = packet_sender.unbounded_send((*kind, u64::from_le_bytes(time)));
+                }
+            }
+            Box::pin(async {})
+        }));
+        Heartbeat {
+            data_channel,
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(5),
+            packets: AsyncMutex::new(packets),
+            stats: Mutex::new(RttStats::default()),
+        }
+    }
+
+    /// How often to ping, every second by default and at most every 10 milliseconds.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(MIN_INTERVAL);
+        self
+    }
+
+    /// How long the remote peer may be silent before it is considered gone, 5 seconds by
+    /// default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn stats(&self) -> RttStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Pings the remote peer and answers its pings until it has been silent for the timeout,
+    /// then returns [`Error::HeartbeatTimedOut`].
+    ///
+    /// The silence is measured from when this is called, so the data channel should already be
+    /// open.
+    pub async fn run(&self) -> Result<(), Error> {
+        let mut packets = self.packets.lock().await;
+        let mut last_received = runtime::now();
+        let mut next_ping = last_received;
+        loop {
+            let now = runtime::now();
+            let timeout_at = last_received + self.timeout;
+            if now >= timeout_at {
+                return Err(Error::HeartbeatTimedOut);
+            }
+            if now >= next_ping {
+                // failed sends are noticed as silence
+                _ = self
+                    .data_channel
+                    .send(&packet(PING, now.as_micros() as u64))
+                    .await;
+                next_ping = now + self.interval;
+            }
+            let wait = next_ping.min(timeout_at).saturating_sub(now);
+            let Some(received) = runtime::timeout(Some(wait), packets.next()).await else {
+                continue;
+            };
+            let Some((kind, time)) = received else {
+                return Err(Error::HeartbeatTimedOut);
+            };
+            let now = runtime::now();
+            last_received = now;
+            match kind {
+                PING => {
+                    _ = self.data_channel.send(&packet(PONG, time)).await;
+                }
+                _ => {
+                    let rtt = now.saturating_sub(Duration::from_micros(time));
+                    self.stats.lock().unwrap().record(rtt);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Heartbeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Heartbeat")
+            .field("interval", &self.interval)
+            .field("timeout", &self.timeout)
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A ping carries the sender's time, which a pong echoes back unchanged.
+fn packet(kind: u8, time: u64) -> [u8; 9] {
+    let mut packet = [kind; 9];
+    packet[1..].copy_from_slice(&time.to_le_bytes());
+    packet
+}
@@ -0,0 +1,495 @@
+//! WHIP-style offer/answer signaling over plain HTTP(S).
+//!
+//! The client `POST`s an SDP offer (`application/sdp`) and receives the answer in a
+//! `201 Created` response whose `Location` header names the session resource. Trickled
+//! candidates are sent to that resource with `PATCH` (`application/trickle-ice-sdpfrag`) and
+//! the session is torn down with `DELETE`.
+
+use crate::{Error, IceCandidateInit, SessionDescription};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::*;
+
+const SDP_CONTENT_TYPE: &str = "application/sdp";
+const SDP_FRAGMENT_CONTENT_TYPE: &str = "application/trickle-ice-sdpfrag";
+
+#[derive(Debug, Clone)]
+pub struct HttpSignalingClient {
+    client: reqwest::Client,
+    endpoint: String,
+    bearer_token: Option<String>,
+}
+
+impl HttpSignalingClient {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.into(),
+            bearer_token: None,
+        }
+    }
+
+    pub fn with_bearer_token(mut self, bearer_token: impl Into<String>) -> Self {
+        self.bearer_token = Some(bearer_token.into());
+        self
+    }
+
+    /// Sends `offer` to the endpoint and returns the created session along with the answer.
+    pub async fn send_offer(
+        &self,
+        offer: &SessionDescription,
+    ) -> Result<(HttpSession, SessionDescription), Error> {
+        let response = self
+            .request(reqwest::Method::POST, &self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, SDP_CONTENT_TYPE)
+            .body(offer.sdp())
+            .send()
+            .await
+            .map_err(|_| Error::HttpRequestFailed)?;
+        if !response.status().is_success() {
+            return Err(Error::HttpRequestFailed);
+        }
+        let url = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| response.url().join(location).ok())
+            .ok_or(Error::HttpRequestFailed)?;
+        let answer = response
+            .text()
+            .await
+            .map_err(|_| Error::HttpRequestFailed)?;
+        Ok((
+            HttpSession {
+                client: self.clone(),
+                url: url.to_string(),
+                mid: sdp_mid(&offer.sdp()),
+            },
+            SessionDescription::answer(&answer)?,
+        ))
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.bearer_token {
+            Some(bearer_token) => request.bearer_auth(bearer_token),
+            None => request,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpSession {
+    client: HttpSignalingClient,
+    url: String,
+    mid: Option<String>,
+}
+
+impl HttpSession {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Trickles a local candidate to the remote peer, or signals the end of candidates if
+    /// `ice_candidate` is `None`.
+    pub async fn add_ice_candidate(
+        &self,
+        ice_candidate: Option<IceCandidateInit>,
+    ) -> Result<(), Error> {
+        let mut fragment = String::new();
+        fragment.push_str("m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n");
+        let mid = ice_candidate
+            .as_ref()
+            .and_then(|ice_candidate| ice_candidate.sdp_mid.clone())
+            .or_else(|| self.mid.clone())
+            .unwrap_or_else(|| "0".to_owned());
+        fragment.push_str(&format!("a=mid:{}\r\n", mid));
+        match ice_candidate {
+            Some(ice_candidate) => {
+                let candidate = ice_candidate.candidate.trim_start_matches("a=");
+                fragment.push_str(&format!("a={}\r\n", candidate));
+            }
+            None => fragment.push_str("a=end-of-candidates\r\n"),
+        }
+        let response = self
+            .client
+            .request(reqwest::Method::PATCH, &self.url)
+            .header(reqwest::header::CONTENT_TYPE, SDP_FRAGMENT_CONTENT_TYPE)
+            .body(fragment)
+            .send()
+            .await
+            .map_err(|_| Error::HttpRequestFailed)?;
+        if !response.status().is_success() {
+            return Err(Error::HttpRequestFailed);
+        }
+        Ok(())
+    }
+
+    pub async fn close(&self) -> Result<(), Error> {
+        let response = self
+            .client
+            .request(reqwest::Method::DELETE, &self.url)
+            .send()
+            .await
+            .map_err(|_| Error::HttpRequestFailed)?;
+        if !response.status().is_success() {
+            return Err(Error::HttpRequestFailed);
+        }
+        Ok(())
+    }
+}
+
+fn sdp_mid(sdp: &str) -> Option<String> {
+    sdp.lines()
+        .find_map(|line| line.strip_prefix("a=mid:"))
+        .map(|mid| mid.to_owned())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use std::{
+        collections::HashMap,
+        convert::Infallible,
+        future::Future,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+    use hyper::{
+        body::{Bytes, Incoming},
+        header,
+        server::conn::http1,
+        service::service_fn,
+        Method, Request, Response, StatusCode,
+    };
+    use hyper_util::rt::TokioIo;
+
+    use super::SDP_CONTENT_TYPE;
+    use crate::{
+        Configuration, Error, IceCandidateInit, PeerConnection, PeerConnectionState,
+        SessionDescription,
+    };
+
+    /// Request bodies larger than this are rejected with `413 Payload Too Large`.
+    const MAX_BODY_SIZE: usize = 64 * 1024;
+
+    pub type OnHttpPeerFn = Box<
+        dyn Fn(Arc<PeerConnection>) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+            + Send
+            + Sync,
+    >;
+
+    /// Answers offers received over HTTP, creating a [`PeerConnection`] for each one.
+    ///
+    /// `on_peer` is called for every new peer before the offer is applied, so that handlers
+    /// such as [`PeerConnection::on_data_channel`] can be registered in time. The server
+    /// keeps each peer alive until its session is deleted or the peer fails or closes. It
+    /// registers [`PeerConnection::on_connection_state_change`] itself to notice this, so
+    /// `on_peer` must not replace that handler.
+    ///
+    /// Requests are accepted from anyone unless a bearer token is required with
+    /// [`HttpSignalingServer::with_bearer_token`].
+    #[derive(Clone)]
+    pub struct HttpSignalingServer {
+        inner: Arc<HttpSignalingServerInner>,
+        bearer_token: Option<Arc<str>>,
+    }
+
+    struct HttpSignalingServerInner {
+        configuration: Configuration,
+        on_peer: OnHttpPeerFn,
+        gathering_timeout: Option<Duration>,
+        sessions: Mutex<HashMap<String, Arc<PeerConnection>>>,
+        next_session_id: AtomicU64,
+    }
+
+    impl HttpSignalingServer {
+        pub fn new(configuration: Configuration, on_peer: OnHttpPeerFn) -> Self {
+            Self::new_with_gathering_timeout(configuration, on_peer, Some(Duration::from_secs(5)))
+        }
+
+        pub fn new_with_gathering_timeout(
+            configuration: Configuration,
+            on_peer: OnHttpPeerFn,
+            gathering_timeout: Option<Duration>,
+        ) -> Self {
+            Self {
+                inner: Arc::new(HttpSignalingServerInner {
+                    configuration,
+                    on_peer,
+                    gathering_timeout,
+                    sessions: Mutex::new(HashMap::new()),
+                    next_session_id: AtomicU64::new(1),
+                }),
+                bearer_token: None,
+            }
+        }
+
+        /// Rejects requests made by [`HttpSignalingServer::serve`] without this token with
+        /// `401 Unauthorized`.
+        pub fn with_bearer_token(mut self, bearer_token: impl Into<String>) -> Self {
+            self.bearer_token = Some(bearer_token.into().into());
+            self
+        }
+
+        /// Creates a peer for `offer`, returning its session id and the answer. The answer is
+        /// created with ICE gathering complete, so clients do not need to poll for candidates.
+        pub async fn handle_offer(
+            &self,
+            offer: &str,
+        ) -> Result<(String, SessionDescription), Error> {
+            let offer = SessionDescription::offer(offer)?;
+            let peer = Arc::new(PeerConnection::new(&self.inner.configuration).await?);
+            (self.inner.on_peer)(peer.clone()).await;
+            let session_id = self
+                .inner
+                .next_session_id
+                .fetch_add(1, Ordering::Relaxed)
+                .to_string();
+            // tracked before the offer is applied, so a peer failing at any point is forgotten
+            self.inner
+                .sessions
+                .lock()
+                .unwrap()
+                .insert(session_id.clone(), peer.clone());
+            {
+                // weak, so the peer's handler does not keep the server alive
+                let inner = Arc::downgrade(&self.inner);
+                let session_id = session_id.clone();
+                peer.on_connection_state_change(Box::new(move |state| {
+                    if matches!(
+                        state,
+                        PeerConnectionState::Failed | PeerConnectionState::Closed
+                    ) {
+                        let peer = inner
+                            .upgrade()
+                            .and_then(|inner| inner.sessions.lock().unwrap().remove(&session_id));
+                        // closing from within the peer's own handler could deadlock
+                        if let Some(peer) = peer {
+                            tokio::spawn(async move {
+                                _ = peer.close().await;
+                            });
+                        }
+                    }
+                    Box::pin(async {})
+                }));
+            }
+            match self.answer(&peer, &offer).await {
+                Ok(answer) => Ok((session_id, answer)),
+                Err(err) => {
+                    self.inner.sessions.lock().unwrap().remove(&session_id);
+                    _ = peer.close().await;
+                    Err(err)
+                }
+            }
+        }
+
+        /// Adds the candidates of a trickle ICE SDP fragment to the session's peer.
+        pub async fn handle_patch(&self, session_id: &str, fragment: &str) -> Result<(), Error> {
+            let peer = self.session(session_id)?;
+            for ice_candidate in parse_sdp_fragment(fragment)? {
+                peer.add_ice_candidate(ice_candidate).await?;
+            }
+            Ok(())
+        }
+
+        /// Closes the session's peer and forgets it.
+        pub async fn handle_delete(&self, session_id: &str) -> Result<(), Error> {
+            let peer = self
+                .inner
+                .sessions
+                .lock()
+                .unwrap()
+                .remove(session_id)
+                .ok_or(Error::UnknownSession)?;
+            peer.close().await
+        }
+
+        /// Serves signaling requests on `listener` until accepting a connection fails.
+        ///
+        /// Offers may be posted to any path, and the session resource is created beneath it.
+        pub async fn serve(&self, listener: std::net::TcpListener) -> std::io::Result<()> {
+            listener.set_nonblocking(true)?;
+            let listener = tokio::net::TcpListener::from_std(listener)?;
+            loop {
+                let (stream, _) = listener.accept().await?;
+                let server = self.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| {
+                        let server = server.clone();
+                        async move { Ok::<_, Infallible>(server.route(request).await) }
+                    });
+                    _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        }
+
+        async fn answer(
+            &self,
+            peer: &PeerConnection,
+            offer: &SessionDescription,
+        ) -> Result<SessionDescription, Error> {
+            peer.set_remote_description(offer).await?;
+            peer.create_answer_complete(self.inner.gathering_timeout)
+                .await
+        }
+
+        fn session(&self, session_id: &str) -> Result<Arc<PeerConnection>, Error> {
+            self.inner
+                .sessions
+                .lock()
+                .unwrap()
+                .get(session_id)
+                .cloned()
+                .ok_or(Error::UnknownSession)
+        }
+
+        async fn route(&self, request: Request<Incoming>) -> Response<Full<Bytes>> {
+            if let Some(bearer_token) = &self.bearer_token {
+                let authorized = request
+                    .headers()
+                    .get(header::AUTHORIZATION)
+                    .and_then(|authorization| authorization.to_str().ok())
+                    .and_then(|authorization| authorization.strip_prefix("Bearer "))
+                    .is_some_and(|token| constant_time_eq(token, bearer_token));
+                if !authorized {
+                    return status(StatusCode::UNAUTHORIZED);
+                }
+            }
+            let method = request.method().clone();
+            let path = request.uri().path().trim_end_matches('/').to_owned();
+            let body = match Limited::new(request.into_body(), MAX_BODY_SIZE)
+                .collect()
+                .await
+            {
+                Ok(body) => body,
+                Err(err) if err.downcast_ref::<LengthLimitError>().is_some() => {
+                    return status(StatusCode::PAYLOAD_TOO_LARGE);
+                }
+                Err(_) => return status(StatusCode::BAD_REQUEST),
+            };
+            let Ok(body) = String::from_utf8(body.to_bytes().to_vec()) else {
+                return status(StatusCode::BAD_REQUEST);
+            };
+            let session_id = path.rsplit('/').next().unwrap_or_default();
+            let result = match method {
+                Method::POST => {
+                    return match self.handle_offer(&body).await {
+                        Ok((session_id, answer)) => Response::builder()
+                            .status(StatusCode::CREATED)
+                            .header(header::CONTENT_TYPE, SDP_CONTENT_TYPE)
+                            .header(header::LOCATION, format!("{}/{}", path, session_id))
+                            .body(Full::new(Bytes::from(answer.sdp())))
+                            .unwrap(),
+                        Err(_) => status(StatusCode::BAD_REQUEST),
+                    };
+                }
+                Method::PATCH => self.handle_patch(session_id, &body).await,
+                Method::DELETE => self.handle_delete(session_id).await,
+                _ => return status(StatusCode::METHOD_NOT_ALLOWED),
+            };
+            match result {
+                Ok(()) => status(StatusCode::NO_CONTENT),
+                Err(Error::UnknownSession) => status(StatusCode::NOT_FOUND),
+                Err(_) => status(StatusCode::BAD_REQUEST),
+            }
+        }
+    }
+
+    /// Parses the candidates out of a trickle ICE SDP fragment. `None` marks the end of
+    /// candidates.
+    fn parse_sdp_fragment(fragment: &str) -> Result<Vec<Option<IceCandidateInit>>, Error> {
+        let mut ice_candidates = vec![];
+        let mut mid = None;
+        let mut mline_index = None;
+        for line in fragment.lines() {
+            if line.starts_with("m=") {
+                mline_index = Some(match mline_index {
+                    Some(index) => u16::checked_add(index, 1).ok_or(Error::FailedToParseSdp)?,
+                    None => 0,
+                });
+            } else if let Some(value) = line.strip_prefix("a=mid:") {
+                mid = Some(value.to_owned());
+            } else if let Some(candidate) = line.strip_prefix("a=") {
+                if candidate.starts_with("candidate:") {
+                    ice_candidates.push(Some(IceCandidateInit {
+                        candidate: candidate.to_owned(),
+                        sdp_mid: mid.clone(),
+                        sdp_mline_index: mline_index,
+                    }));
+                } else if candidate == "end-of-candidates" {
+                    ice_candidates.push(None);
+                }
+            }
+        }
+        Ok(ice_candidates)
+    }
+
+    fn constant_time_eq(a: &str, b: &str) -> bool {
+        a.len() == b.len()
+            && a.bytes()
+                .zip(b.bytes())
+                .fold(0, |difference, (a, b)| difference | (a ^ b))
+                == 0
+    }
+
+    fn status(status: StatusCode) -> Response<Full<Bytes>> {
+        Response::builder()
+            .status(status)
+            .body(Full::new(Bytes::new()))
+            .unwrap()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn sdp_fragments_are_parsed() {
+            let fragment = "a=ice-ufrag:EsAw\r\n\
+                            a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+                            m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n\
+                            a=mid:0\r\n\
+                            a=candidate:1 1 UDP 2122252543 192.168.1.20 50000 typ host\r\n\
+                            a=end-of-candidates\r\n";
+            assert_eq!(
+                parse_sdp_fragment(fragment).unwrap(),
+                vec![
+                    Some(IceCandidateInit {
+                        candidate: "candidate:1 1 UDP 2122252543 192.168.1.20 50000 typ host"
+                            .to_owned(),
+                        sdp_mid: Some("0".to_owned()),
+                        sdp_mline_index: Some(0),
+                    }),
+                    None,
+                ]
+            );
+        }
+
+        #[test]
+        fn too_many_media_sections_are_rejected() {
+            let fragment = "m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n".repeat(65537);
+            assert!(matches!(
+                parse_sdp_fragment(&fragment),
+                Err(Error::FailedToParseSdp)
+            ));
+            let fragment = "m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n".repeat(65536);
+            assert!(parse_sdp_fragment(&fragment).unwrap().is_empty());
+        }
+
+        #[test]
+        fn tokens_are_compared_exactly() {
+            assert!(constant_time_eq("secret", "secret"));
+            assert!(!constant_time_eq("secret", "secreT"));
+            assert!(!constant_time_eq("secret", "secrets"));
+            assert!(!constant_time_eq("", "secret"));
+        }
+    }
+}
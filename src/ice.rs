@@ -0,0 +1,351 @@
+//! ICE server URL parsing, following RFC 7064 (`stun:`/`stuns:`) and RFC 7065
+//! (`turn:`/`turns:`).
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum IceScheme {
+    Stun,
+    Stuns,
+    Turn,
+    Turns,
+}
+
+impl IceScheme {
+    pub fn is_turn(self) -> bool {
+        matches!(self, IceScheme::Turn | IceScheme::Turns)
+    }
+
+    pub fn is_secure(self) -> bool {
+        matches!(self, IceScheme::Stuns | IceScheme::Turns)
+    }
+
+    /// 3478, or 5349 for the TLS schemes.
+    pub fn default_port(self) -> u16 {
+        if self.is_secure() {
+            5349
+        } else {
+            3478
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            IceScheme::Stun => "stun",
+            IceScheme::Stuns => "stuns",
+            IceScheme::Turn => "turn",
+            IceScheme::Turns => "turns",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum IceTransport {
+    Udp,
+    Tcp,
+}
+
+/// A parsed ICE server URL, such as `stun:stun.l.google.com:19302` or
+/// `turn:turn.example.com?transport=tcp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IceUrl {
+    pub scheme: IceScheme,
+    /// Hostname or IP address. IPv6 addresses are stored without brackets.
+    pub host: String,
+    pub port: Option<u16>,
+    /// The `?transport=` parameter, only allowed for TURN URLs.
+    pub transport: Option<IceTransport>,
+}
+
+impl IceUrl {
+    pub fn port_or_default(&self) -> u16 {
+        self.port.unwrap_or(self.scheme.default_port())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum IceUrlError {
+    /// The scheme is not one of `stun`, `stuns`, `turn` or `turns`.
+    #[error("Unknown scheme.")]
+    UnknownScheme,
+    #[error("Missing host.")]
+    MissingHost,
+    #[error("Invalid host.")]
+    InvalidHost,
+    #[error("Invalid port.")]
+    InvalidPort,
+    /// The query is not `transport=udp` or `transport=tcp`.
+    #[error("Invalid transport.")]
+    InvalidTransport,
+    /// STUN URLs do not take a query.
+    #[error("Unexpected query.")]
+    UnexpectedQuery,
+}
+
+impl FromStr for IceUrl {
+    type Err = IceUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = s.split_once(':').ok_or(IceUrlError::UnknownScheme)?;
+        let scheme = match scheme.to_ascii_lowercase().as_str() {
+            "stun" => IceScheme::Stun,
+            "stuns" => IceScheme::Stuns,
+            "turn" => IceScheme::Turn,
+            "turns" => IceScheme::Turns,
+            _ => return Err(IceUrlError::UnknownScheme),
+        };
+        let (authority, query) = match rest.split_once('?') {
+            Some((authority, query)) => (authority, Some(query)),
+            None => (rest, None),
+        };
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']').ok_or(IceUrlError::InvalidHost)?;
+            if host.parse::<std::net::Ipv6Addr>().is_err() {
+                return Err(IceUrlError::InvalidHost);
+            }
+            let port = match after {
+                "" => None,
+                _ => Some(after.strip_prefix(':').ok_or(IceUrlError::InvalidHost)?),
+            };
+            (host, port)
+        } else {
+            match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+        if host.is_empty() {
+            return Err(IceUrlError::MissingHost);
+        }
+        // rejects `stun://host` and other URL-ish typos
+        if !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':'))
+        {
+            return Err(IceUrlError::InvalidHost);
+        }
+        let port = port
+            .map(|port| match port.parse::<u16>() {
+                Ok(port) if port != 0 => Ok(port),
+                _ => Err(IceUrlError::InvalidPort),
+            })
+            .transpose()?;
+        let transport = match query {
+            None => None,
+            Some(_) if !scheme.is_turn() => return Err(IceUrlError::UnexpectedQuery),
+            Some(query) => match query.strip_prefix("transport=") {
+                Some(transport) if transport.eq_ignore_ascii_case("udp") => Some(IceTransport::Udp),
+                Some(transport) if transport.eq_ignore_ascii_case("tcp") => Some(IceTransport::Tcp),
+                _ => return Err(IceUrlError::InvalidTransport),
+            },
+        };
+        Ok(IceUrl {
+            scheme,
+            host: host.to_owned(),
+            port,
+            transport,
+        })
+    }
+}
+
+impl fmt::Display for IceUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.scheme.as_str())?;
+        if self.host.contains(':') {
+            write!(f, "[{}]", self.host)?;
+        } else {
+            write!(f, "{}", self.host)?;
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        match self.transport {
+            Some(IceTransport::Udp) => write!(f, "?transport=udp")?,
+            Some(IceTransport::Tcp) => write!(f, "?transport=tcp")?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Configuration, ConfigurationError, IceServer};
+
+    fn url(scheme: IceScheme, host: &str, port: Option<u16>) -> IceUrl {
+        IceUrl {
+            scheme,
+            host: host.to_owned(),
+            port,
+            transport: None,
+        }
+    }
+
+    #[test]
+    fn valid_urls() {
+        let cases = [
+            (
+                "stun:stun.l.google.com:19302",
+                url(IceScheme::Stun, "stun.l.google.com", Some(19302)),
+            ),
+            (
+                "stuns:stun.example.com",
+                url(IceScheme::Stuns, "stun.example.com", None),
+            ),
+            (
+                "STUN:192.0.2.1:3478",
+                url(IceScheme::Stun, "192.0.2.1", Some(3478)),
+            ),
+            (
+                "stun:[2001:db8::1]:3478",
+                url(IceScheme::Stun, "2001:db8::1", Some(3478)),
+            ),
+            ("turn:[::1]", url(IceScheme::Turn, "::1", None)),
+            (
+                "turn:turn.example.com?transport=tcp",
+                IceUrl {
+                    transport: Some(IceTransport::Tcp),
+                    ..url(IceScheme::Turn, "turn.example.com", None)
+                },
+            ),
+            (
+                "turns:turn.example.com:443?transport=TCP",
+                IceUrl {
+                    transport: Some(IceTransport::Tcp),
+                    ..url(IceScheme::Turns, "turn.example.com", Some(443))
+                },
+            ),
+            (
+                "turn:turn.example.com:3478?transport=udp",
+                IceUrl {
+                    transport: Some(IceTransport::Udp),
+                    ..url(IceScheme::Turn, "turn.example.com", Some(3478))
+                },
+            ),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(text.parse::<IceUrl>(), Ok(expected.clone()), "{}", text);
+            assert_eq!(expected.to_string().parse::<IceUrl>(), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn invalid_urls() {
+        let cases = [
+            ("stun.l.google.com:19302", IceUrlError::UnknownScheme),
+            ("http://stun.example.com", IceUrlError::UnknownScheme),
+            ("sturn:stun.example.com", IceUrlError::UnknownScheme),
+            ("stun:", IceUrlError::MissingHost),
+            ("turn::3478", IceUrlError::MissingHost),
+            ("stun://stun.example.com", IceUrlError::InvalidHost),
+            ("stun:[stun.example.com]", IceUrlError::InvalidHost),
+            ("stun:[::1", IceUrlError::InvalidHost),
+            ("stun:[::1]3478", IceUrlError::InvalidHost),
+            ("stun:stun.example.com:0", IceUrlError::InvalidPort),
+            ("stun:stun.example.com:65536", IceUrlError::InvalidPort),
+            ("stun:stun.example.com:port", IceUrlError::InvalidPort),
+            (
+                "turn:turn.example.com?transport=sctp",
+                IceUrlError::InvalidTransport,
+            ),
+            (
+                "turn:turn.example.com?protocol=tcp",
+                IceUrlError::InvalidTransport,
+            ),
+            (
+                "stun:stun.example.com?transport=udp",
+                IceUrlError::UnexpectedQuery,
+            ),
+        ];
+        for (text, error) in cases {
+            assert_eq!(text.parse::<IceUrl>(), Err(error), "{}", text);
+        }
+    }
+
+    #[test]
+    fn default_ports() {
+        assert_eq!(url(IceScheme::Stun, "host", None).port_or_default(), 3478);
+        assert_eq!(url(IceScheme::Turn, "host", None).port_or_default(), 3478);
+        assert_eq!(url(IceScheme::Stuns, "host", None).port_or_default(), 5349);
+        assert_eq!(url(IceScheme::Turns, "host", None).port_or_default(), 5349);
+        assert_eq!(
+            url(IceScheme::Turns, "host", Some(443)).port_or_default(),
+            443
+        );
+    }
+
+    #[test]
+    fn ice_server_constructors() {
+        assert_eq!(
+            IceServer::stun("stun.l.google.com", 19302).urls,
+            ["stun:stun.l.google.com:19302"]
+        );
+        assert_eq!(IceServer::stun("[::1]", 3478).urls, ["stun:[::1]:3478"]);
+        assert_eq!(IceServer::stun("::1", 3478).urls, ["stun:[::1]:3478"]);
+        let turn = IceServer::turn("turn:turn.example.com?transport=tcp", "user", "pass");
+        assert_eq!(turn.username.as_deref(), Some("user"));
+        assert_eq!(turn.credential.as_deref(), Some("pass"));
+    }
+
+    #[test]
+    fn configuration_validation() {
+        let configuration = |ice_servers| Configuration {
+            ice_servers,
+            ..Default::default()
+        };
+        assert_eq!(configuration(vec![]).validate(), Ok(()));
+        assert_eq!(
+            configuration(vec![
+                IceServer::stun("stun.l.google.com", 19302),
+                IceServer::turn("turns:turn.example.com", "user", "pass"),
+            ])
+            .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            configuration(vec![IceServer::default()]).validate(),
+            Err(ConfigurationError::MissingUrls { ice_server: 0 })
+        );
+        assert_eq!(
+            configuration(vec![
+                IceServer::stun("stun.l.google.com", 19302),
+                IceServer {
+                    urls: vec!["turn:turn.example.com?transport=tls".to_owned()],
+                    ..IceServer::turn("", "user", "pass")
+                },
+            ])
+            .validate(),
+            Err(ConfigurationError::InvalidUrl {
+                ice_server: 1,
+                url: "turn:turn.example.com?transport=tls".to_owned(),
+                error: IceUrlError::InvalidTransport,
+            })
+        );
+        for (username, credential) in [
+            (None, Some("pass")),
+            (Some(""), Some("pass")),
+            (Some("user"), None),
+        ] {
+            assert_eq!(
+                configuration(vec![IceServer {
+                    urls: vec!["turn:turn.example.com".to_owned()],
+                    username: username.map(str::to_owned),
+                    credential: credential.map(str::to_owned),
+                    ..Default::default()
+                }])
+                .validate(),
+                Err(ConfigurationError::MissingCredentials {
+                    ice_server: 0,
+                    url: "turn:turn.example.com".to_owned(),
+                })
+            );
+        }
+    }
+}
@@ -1,28 +1,76 @@
 #![allow(missing_docs)]
 
-use std::{collections::HashMap, future::Future, pin::Pin};
+use std::{collections::HashMap, future::Future, pin::Pin, time::Duration};
 
 use maybe_sync::{dyn_maybe_send, dyn_maybe_send_sync};
 use thiserror::Error;
 
 #[cfg(not(target_arch = "wasm32"))]
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+mod clock;
+pub mod compact;
+mod factory;
+mod heartbeat;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod ice;
+mod listener;
+mod privacy;
+mod reconnect;
+mod runtime;
+pub mod sdp;
+mod server;
+mod settings;
+mod signal;
+pub mod turn;
+
+pub use clock::{ClockEstimate, ClockSync};
+pub use factory::PeerFactory;
+pub use heartbeat::{Heartbeat, RttStats};
+pub use listener::{AcceptedDataChannel, AcceptedPeer, IncomingOffer, OfferStream, RtcListener};
+pub use privacy::IpPrivacy;
+pub use reconnect::{Backoff, ReconnectEvent, ReconnectingPeer, SendOfferFn};
+pub use server::{ClientId, Server, ServerEvent};
+pub use settings::{
+    IceTimeouts, InterfaceFilterFn, IpFilterFn, MdnsMode, Nat1To1CandidateType, NativeSettings,
+    NetworkType, UdpMux,
+};
+pub use signal::SignalMessage;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod native {
     pub use webrtc::{
         api::API,
         data_channel::{data_channel_init::RTCDataChannelInit, RTCDataChannel},
-        ice::candidate::{CandidatePairState, CandidateType},
+        ice::{
+            candidate::{CandidatePairState, CandidateType},
+            network_type::NetworkType,
+        },
         ice_transport::{
             ice_candidate::{RTCIceCandidate, RTCIceCandidateInit},
+            ice_candidate_type::RTCIceCandidateType,
+            ice_connection_state::RTCIceConnectionState,
             ice_credential_type::RTCIceCredentialType,
+            ice_gatherer_state::RTCIceGathererState,
+            ice_gathering_state::RTCIceGatheringState,
             ice_server::RTCIceServer,
         },
         peer_connection::{
-            configuration::RTCConfiguration, peer_connection_state::RTCPeerConnectionState,
-            policy::ice_transport_policy::RTCIceTransportPolicy,
-            sdp::session_description::RTCSessionDescription, RTCPeerConnection,
+            certificate::RTCCertificate,
+            configuration::RTCConfiguration,
+            offer_answer_options::RTCOfferOptions,
+            peer_connection_state::RTCPeerConnectionState,
+            policy::{
+                bundle_policy::RTCBundlePolicy, ice_transport_policy::RTCIceTransportPolicy,
+                rtcp_mux_policy::RTCRtcpMuxPolicy,
+            },
+            sdp::{sdp_type::RTCSdpType, session_description::RTCSessionDescription},
+            signaling_state::RTCSignalingState,
+            RTCPeerConnection,
         },
         stats::StatsReportType,
     };
@@ -33,9 +81,11 @@ mod wasm {
     pub use wasm_bindgen::{closure::Closure, JsValue};
     pub use wasm_bindgen_futures::{future_to_promise, JsFuture};
     pub use web_sys::{
-        RtcConfiguration, RtcDataChannel, RtcDataChannelInit, RtcDataChannelType, RtcIceCandidate,
-        RtcIceCandidateInit, RtcIceTransportPolicy, RtcPeerConnection, RtcPeerConnectionState,
-        RtcSdpType, RtcSessionDescription, RtcSessionDescriptionInit, RtcStatsReport, TextEncoder,
+        RtcBundlePolicy, RtcCertificate, RtcConfiguration, RtcDataChannel, RtcDataChannelInit,
+        RtcDataChannelType, RtcIceCandidate, RtcIceCandidateInit, RtcIceConnectionState,
+        RtcIceGatheringState, RtcIceTransportPolicy, RtcOfferOptions, RtcPeerConnection,
+        RtcPeerConnectionState, RtcSdpType, RtcSessionDescription, RtcSessionDescriptionInit,
+        RtcSignalingState, RtcStatsReport, TextEncoder,
     };
 }
 
@@ -55,6 +105,23 @@ pub type OnPeerConnectionStateChangeFn = Box<
         (Fn(PeerConnectionState) -> Pin<Box<dyn_maybe_send!(Future<Output = ()> + 'static)>>)
     ),
 >;
+pub type OnNegotiationNeededFn =
+    Box<dyn_maybe_send_sync!((Fn() -> Pin<Box<dyn_maybe_send!(Future<Output = ()> + 'static)>>))>;
+pub type OnSignalingStateChangeFn = Box<
+    dyn_maybe_send_sync!(
+        (Fn(SignalingState) -> Pin<Box<dyn_maybe_send!(Future<Output = ()> + 'static)>>)
+    ),
+>;
+pub type OnIceConnectionStateChangeFn = Box<
+    dyn_maybe_send_sync!(
+        (Fn(IceConnectionState) -> Pin<Box<dyn_maybe_send!(Future<Output = ()> + 'static)>>)
+    ),
+>;
+pub type OnIceGatheringStateChangeFn = Box<
+    dyn_maybe_send_sync!(
+        (Fn(IceGatheringState) -> Pin<Box<dyn_maybe_send!(Future<Output = ()> + 'static)>>)
+    ),
+>;
 pub type OnIceCandidateFn = Box<
     dyn_maybe_send_sync!(
         (Fn(Option<IceCandidate>) -> Pin<Box<dyn_maybe_send!(Future<Output = ()> + 'static)>>)
@@ -75,6 +142,36 @@ pub struct IceServer {
     pub credential_type: IceCredentialType,
 }
 
+impl IceServer {
+    /// A STUN server at `host:port`.
+    pub fn stun(host: &str, port: u16) -> Self {
+        IceServer {
+            urls: vec![ice::IceUrl {
+                scheme: ice::IceScheme::Stun,
+                host: host
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_owned(),
+                port: Some(port),
+                transport: None,
+            }
+            .to_string()],
+            ..Default::default()
+        }
+    }
+
+    /// A TURN server authenticated with a username and password, e.g.
+    /// `IceServer::turn("turn:turn.example.com?transport=tcp", "user", "pass")`.
+    pub fn turn(url: &str, username: &str, credential: &str) -> Self {
+        IceServer {
+            urls: vec![url.to_owned()],
+            username: Some(username.to_owned()),
+            credential: Some(credential.to_owned()),
+            credential_type: IceCredentialType::Password,
+        }
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IceCredentialType {
@@ -92,17 +189,210 @@ pub enum IceTransportPolicy {
     Relay,
 }
 
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BundlePolicy {
+    #[default]
+    Balanced,
+    MaxCompat,
+    MaxBundle,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RtcpMuxPolicy {
+    /// Not supported in browsers, nor on native where webrtc-rs always multiplexes RTCP.
+    Negotiate,
+    #[default]
+    Require,
+}
+
+#[derive(Clone)]
+pub struct Certificate(
+    #[cfg(not(target_arch = "wasm32"))] native::RTCCertificate,
+    #[cfg(target_arch = "wasm32")] wasm::RtcCertificate,
+);
+
+impl Certificate {
+    /// Generates a new ECDSA P-256 certificate.
+    pub async fn generate() -> Result<Self, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let key_pair =
+                rcgen::KeyPair::generate().map_err(|_| Error::FailedToGenerateCertificate)?;
+            Ok(Certificate(
+                native::RTCCertificate::from_key_pair(key_pair)
+                    .map_err(|_| Error::FailedToGenerateCertificate)?,
+            ))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let algorithm = wasm::Object::new();
+            wasm::Reflect::set(&algorithm, &"name".into(), &"ECDSA".into()).unwrap();
+            wasm::Reflect::set(&algorithm, &"namedCurve".into(), &"P-256".into()).unwrap();
+            let promise = wasm::RtcPeerConnection::generate_certificate_with_object(&algorithm)
+                .map_err(|_| Error::FailedToGenerateCertificate)?;
+            Ok(Certificate(wasm::RtcCertificate::from(
+                wasm::JsFuture::from(promise)
+                    .await
+                    .map_err(|_| Error::FailedToGenerateCertificate)?,
+            )))
+        }
+    }
+
+    /// Loads a certificate and its private key from PEM, as produced by [`Certificate::to_pem`].
+    ///
+    /// Browsers do not allow importing certificates, so this is unsupported on wasm.
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Ok(Certificate(
+                native::RTCCertificate::from_pem(pem)
+                    .map_err(|_| Error::FailedToParseCertificate)?,
+            ))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            _ = pem;
+            Err(Error::Unsupported("importing certificates in the browser"))
+        }
+    }
+
+    /// Serializes the certificate and its private key to PEM.
+    ///
+    /// Browsers do not allow exporting certificates, so this is unsupported on wasm.
+    pub fn to_pem(&self) -> Result<String, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Ok(self.0.serialize_pem())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Err(Error::Unsupported("exporting certificates in the browser"))
+        }
+    }
+
+    pub fn fingerprints(&self) -> Vec<sdp::Fingerprint> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .get_fingerprints()
+                .into_iter()
+                .filter_map(|fingerprint| {
+                    format!("{} {}", fingerprint.algorithm, fingerprint.value)
+                        .parse()
+                        .ok()
+                })
+                .collect()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            // `getFingerprints` is not implemented by every browser
+            let Ok(get_fingerprints) = wasm::Reflect::get(&self.0, &"getFingerprints".into())
+            else {
+                return vec![];
+            };
+            let Some(get_fingerprints) = get_fingerprints.dyn_ref::<js_sys::Function>() else {
+                return vec![];
+            };
+            let Ok(fingerprints) = get_fingerprints.call0(&self.0) else {
+                return vec![];
+            };
+            wasm::Array::from(&fingerprints)
+                .iter()
+                .filter_map(|fingerprint| {
+                    let algorithm = wasm::Reflect::get(&fingerprint, &"algorithm".into())
+                        .ok()?
+                        .as_string()?;
+                    let value = wasm::Reflect::get(&fingerprint, &"value".into())
+                        .ok()?
+                        .as_string()?;
+                    format!("{} {}", algorithm, value).parse().ok()
+                })
+                .collect()
+        }
+    }
+}
+
+impl std::fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Certificate")
+            .field("fingerprints", &self.fingerprints())
+            .finish()
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Configuration {
     pub ice_servers: Vec<IceServer>,
     pub ice_transport_policy: IceTransportPolicy,
+    pub bundle_policy: BundlePolicy,
+    pub rtcp_mux_policy: RtcpMuxPolicy,
+    /// Number of ICE candidates to gather before an offer is created. Not supported on native.
+    pub ice_candidate_pool_size: u8,
+    /// Not supported on native, and only honored by some browsers.
+    pub peer_identity: Option<String>,
+    /// DTLS certificates identifying this peer. A random certificate is generated for every
+    /// peer if empty.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub certificates: Vec<Certificate>,
+    pub ip_privacy: IpPrivacy,
+}
+
+impl Configuration {
+    /// Checks that every ICE server URL parses and that TURN servers have credentials.
+    ///
+    /// Peers are only created from valid configurations, but this reports what is wrong.
+    pub fn validate(&self) -> Result<(), ConfigurationError> {
+        for (index, ice_server) in self.ice_servers.iter().enumerate() {
+            if ice_server.urls.is_empty() {
+                return Err(ConfigurationError::MissingUrls { ice_server: index });
+            }
+            for url in &ice_server.urls {
+                let parsed =
+                    url.parse::<ice::IceUrl>()
+                        .map_err(|error| ConfigurationError::InvalidUrl {
+                            ice_server: index,
+                            url: url.clone(),
+                            error,
+                        })?;
+                let has_credentials = ice_server
+                    .username
+                    .as_ref()
+                    .is_some_and(|username| !username.is_empty())
+                    && ice_server.credential.is_some();
+                if parsed.scheme.is_turn() && !has_credentials {
+                    return Err(ConfigurationError::MissingCredentials {
+                        ice_server: index,
+                        url: url.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl From<Configuration> for native::RTCConfiguration {
-    fn from(value: Configuration) -> Self {
-        native::RTCConfiguration {
+impl TryFrom<Configuration> for native::RTCConfiguration {
+    type Error = Error;
+
+    fn try_from(value: Configuration) -> Result<Self, Self::Error> {
+        if value.ice_candidate_pool_size > 0 {
+            return Err(Error::Unsupported("ICE candidate pooling on native"));
+        }
+        if value.peer_identity.is_some() {
+            return Err(Error::Unsupported("peer identity on native"));
+        }
+        if value.rtcp_mux_policy == RtcpMuxPolicy::Negotiate {
+            return Err(Error::Unsupported(
+                "negotiating RTCP multiplexing on native",
+            ));
+        }
+        value.validate().map_err(Error::InvalidConfiguration)?;
+        Ok(native::RTCConfiguration {
             ice_servers: value
                 .ice_servers
                 .into_iter()
@@ -122,14 +412,33 @@ impl From<Configuration> for native::RTCConfiguration {
                 IceTransportPolicy::All => native::RTCIceTransportPolicy::All,
                 IceTransportPolicy::Relay => native::RTCIceTransportPolicy::Relay,
             },
+            bundle_policy: match value.bundle_policy {
+                BundlePolicy::Balanced => native::RTCBundlePolicy::Balanced,
+                BundlePolicy::MaxCompat => native::RTCBundlePolicy::MaxCompat,
+                BundlePolicy::MaxBundle => native::RTCBundlePolicy::MaxBundle,
+            },
+            rtcp_mux_policy: native::RTCRtcpMuxPolicy::Require,
+            certificates: value
+                .certificates
+                .into_iter()
+                .map(|certificate| certificate.0)
+                .collect(),
             ..Default::default()
-        }
+        })
     }
 }
 
 #[cfg(target_arch = "wasm32")]
-impl From<Configuration> for wasm::RtcConfiguration {
-    fn from(value: Configuration) -> Self {
+impl TryFrom<Configuration> for wasm::RtcConfiguration {
+    type Error = Error;
+
+    fn try_from(value: Configuration) -> Result<Self, Self::Error> {
+        if value.rtcp_mux_policy == RtcpMuxPolicy::Negotiate {
+            return Err(Error::Unsupported(
+                "negotiating RTCP multiplexing in the browser",
+            ));
+        }
+        value.validate().map_err(Error::InvalidConfiguration)?;
         let mut configuration = wasm::RtcConfiguration::new();
         let ice_servers = wasm::Array::new();
         for IceServer {
@@ -175,7 +484,33 @@ impl From<Configuration> for wasm::RtcConfiguration {
             IceTransportPolicy::All => wasm::RtcIceTransportPolicy::All,
             IceTransportPolicy::Relay => wasm::RtcIceTransportPolicy::Relay,
         });
-        configuration
+        if !value.certificates.is_empty() {
+            let certificates =
+                value
+                    .certificates
+                    .into_iter()
+                    .fold(wasm::Array::new(), |array, certificate| {
+                        array.push(&certificate.0);
+                        array
+                    });
+            wasm::Reflect::set(&configuration, &"certificates".into(), &certificates).unwrap();
+        }
+        configuration.bundle_policy(match value.bundle_policy {
+            BundlePolicy::Balanced => wasm::RtcBundlePolicy::Balanced,
+            BundlePolicy::MaxCompat => wasm::RtcBundlePolicy::MaxCompat,
+            BundlePolicy::MaxBundle => wasm::RtcBundlePolicy::MaxBundle,
+        });
+        wasm::Reflect::set(&configuration, &"rtcpMuxPolicy".into(), &"require".into()).unwrap();
+        wasm::Reflect::set(
+            &configuration,
+            &"iceCandidatePoolSize".into(),
+            &value.ice_candidate_pool_size.into(),
+        )
+        .unwrap();
+        if let Some(peer_identity) = value.peer_identity {
+            configuration.peer_identity(Some(&peer_identity));
+        }
+        Ok(configuration)
     }
 }
 
@@ -311,6 +646,46 @@ impl DataChannel {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum SdpType {
+    Unspecified,
+    Offer,
+    Pranswer,
+    Answer,
+    Rollback,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<native::RTCSdpType> for SdpType {
+    fn from(value: native::RTCSdpType) -> Self {
+        match value {
+            native::RTCSdpType::Unspecified => Self::Unspecified,
+            native::RTCSdpType::Offer => Self::Offer,
+            native::RTCSdpType::Pranswer => Self::Pranswer,
+            native::RTCSdpType::Answer => Self::Answer,
+            native::RTCSdpType::Rollback => Self::Rollback,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<wasm::RtcSdpType> for SdpType {
+    fn from(value: wasm::RtcSdpType) -> Self {
+        match value {
+            wasm::RtcSdpType::Offer => Self::Offer,
+            wasm::RtcSdpType::Pranswer => Self::Pranswer,
+            wasm::RtcSdpType::Answer => Self::Answer,
+            wasm::RtcSdpType::Rollback => Self::Rollback,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SessionDescription(
     #[cfg(not(target_arch = "wasm32"))] native::RTCSessionDescription,
@@ -318,17 +693,31 @@ pub struct SessionDescription(
 );
 
 impl SessionDescription {
-    pub fn offer(sdp: &str) -> Result<Self, Error> {
+    pub fn new(sdp_type: SdpType, sdp: &str) -> Result<Self, Error> {
         #[cfg(not(target_arch = "wasm32"))]
         {
+            let session_description = match sdp_type {
+                SdpType::Offer => native::RTCSessionDescription::offer(sdp.to_owned()),
+                SdpType::Pranswer => native::RTCSessionDescription::pranswer(sdp.to_owned()),
+                SdpType::Answer => native::RTCSessionDescription::answer(sdp.to_owned()),
+                // webrtc-rs cannot apply a rollback
+                SdpType::Rollback => return Err(Error::Unsupported("rollback on native")),
+                SdpType::Unspecified => return Err(Error::FailedToCreateSessionDescription),
+            };
             Ok(SessionDescription(
-                native::RTCSessionDescription::offer(sdp.to_owned())
-                    .map_err(|_| Error::FailedToCreateSessionDescription)?,
+                session_description.map_err(|_| Error::FailedToCreateSessionDescription)?,
             ))
         }
         #[cfg(target_arch = "wasm32")]
         {
-            let mut init = wasm::RtcSessionDescriptionInit::new(wasm::RtcSdpType::Offer);
+            let sdp_type = match sdp_type {
+                SdpType::Offer => wasm::RtcSdpType::Offer,
+                SdpType::Pranswer => wasm::RtcSdpType::Pranswer,
+                SdpType::Answer => wasm::RtcSdpType::Answer,
+                SdpType::Rollback => wasm::RtcSdpType::Rollback,
+                SdpType::Unspecified => return Err(Error::FailedToCreateSessionDescription),
+            };
+            let mut init = wasm::RtcSessionDescriptionInit::new(sdp_type);
             init.sdp(sdp);
             let session_description =
                 wasm::RtcSessionDescription::new_with_description_init_dict(&init)
@@ -337,22 +726,33 @@ impl SessionDescription {
         }
     }
 
+    pub fn offer(sdp: &str) -> Result<Self, Error> {
+        Self::new(SdpType::Offer, sdp)
+    }
+
     pub fn answer(sdp: &str) -> Result<Self, Error> {
+        Self::new(SdpType::Answer, sdp)
+    }
+
+    pub fn pranswer(sdp: &str) -> Result<Self, Error> {
+        Self::new(SdpType::Pranswer, sdp)
+    }
+
+    /// A description that, when set, rolls back an offer that has not been answered yet.
+    ///
+    /// Only browsers support rollback; native returns [`Error::Unsupported`].
+    pub fn rollback() -> Result<Self, Error> {
+        Self::new(SdpType::Rollback, "")
+    }
+
+    pub fn sdp_type(&self) -> SdpType {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            Ok(SessionDescription(
-                native::RTCSessionDescription::answer(sdp.to_owned())
-                    .map_err(|_| Error::FailedToCreateSessionDescription)?,
-            ))
+            SdpType::from(self.0.sdp_type)
         }
         #[cfg(target_arch = "wasm32")]
         {
-            let mut init = wasm::RtcSessionDescriptionInit::new(wasm::RtcSdpType::Answer);
-            init.sdp(sdp);
-            let session_description =
-                wasm::RtcSessionDescription::new_with_description_init_dict(&init)
-                    .map_err(|_| Error::FailedToCreateSessionDescription)?;
-            Ok(SessionDescription(session_description))
+            SdpType::from(self.0.type_())
         }
     }
 
@@ -368,7 +768,40 @@ impl SessionDescription {
     }
 }
 
+/// Same shape as the browser's `RTCSessionDescriptionInit` JSON.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SessionDescriptionJson {
+    #[serde(rename = "type")]
+    sdp_type: SdpType,
+    sdp: String,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SessionDescription {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SessionDescriptionJson {
+            sdp_type: self.sdp_type(),
+            sdp: self.sdp(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SessionDescription {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = SessionDescriptionJson::deserialize(deserializer)?;
+        SessionDescription::new(json.sdp_type, &json.sdp).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum PeerConnectionState {
     Unspecified,
     New,
@@ -409,10 +842,164 @@ impl From<wasm::RtcPeerConnectionState> for PeerConnectionState {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum SignalingState {
+    Unspecified,
+    Stable,
+    HaveLocalOffer,
+    HaveRemoteOffer,
+    HaveLocalPranswer,
+    HaveRemotePranswer,
+    Closed,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<native::RTCSignalingState> for SignalingState {
+    fn from(value: native::RTCSignalingState) -> Self {
+        match value {
+            native::RTCSignalingState::Unspecified => Self::Unspecified,
+            native::RTCSignalingState::Stable => Self::Stable,
+            native::RTCSignalingState::HaveLocalOffer => Self::HaveLocalOffer,
+            native::RTCSignalingState::HaveRemoteOffer => Self::HaveRemoteOffer,
+            native::RTCSignalingState::HaveLocalPranswer => Self::HaveLocalPranswer,
+            native::RTCSignalingState::HaveRemotePranswer => Self::HaveRemotePranswer,
+            native::RTCSignalingState::Closed => Self::Closed,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<wasm::RtcSignalingState> for SignalingState {
+    fn from(value: wasm::RtcSignalingState) -> Self {
+        match value {
+            wasm::RtcSignalingState::Stable => Self::Stable,
+            wasm::RtcSignalingState::HaveLocalOffer => Self::HaveLocalOffer,
+            wasm::RtcSignalingState::HaveRemoteOffer => Self::HaveRemoteOffer,
+            wasm::RtcSignalingState::HaveLocalPranswer => Self::HaveLocalPranswer,
+            wasm::RtcSignalingState::HaveRemotePranswer => Self::HaveRemotePranswer,
+            wasm::RtcSignalingState::Closed => Self::Closed,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum IceConnectionState {
+    Unspecified,
+    New,
+    Checking,
+    Connected,
+    Completed,
+    Disconnected,
+    Failed,
+    Closed,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<native::RTCIceConnectionState> for IceConnectionState {
+    fn from(value: native::RTCIceConnectionState) -> Self {
+        match value {
+            native::RTCIceConnectionState::Unspecified => Self::Unspecified,
+            native::RTCIceConnectionState::New => Self::New,
+            native::RTCIceConnectionState::Checking => Self::Checking,
+            native::RTCIceConnectionState::Connected => Self::Connected,
+            native::RTCIceConnectionState::Completed => Self::Completed,
+            native::RTCIceConnectionState::Disconnected => Self::Disconnected,
+            native::RTCIceConnectionState::Failed => Self::Failed,
+            native::RTCIceConnectionState::Closed => Self::Closed,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<wasm::RtcIceConnectionState> for IceConnectionState {
+    fn from(value: wasm::RtcIceConnectionState) -> Self {
+        match value {
+            wasm::RtcIceConnectionState::New => Self::New,
+            wasm::RtcIceConnectionState::Checking => Self::Checking,
+            wasm::RtcIceConnectionState::Connected => Self::Connected,
+            wasm::RtcIceConnectionState::Completed => Self::Completed,
+            wasm::RtcIceConnectionState::Disconnected => Self::Disconnected,
+            wasm::RtcIceConnectionState::Failed => Self::Failed,
+            wasm::RtcIceConnectionState::Closed => Self::Closed,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum IceGatheringState {
+    Unspecified,
+    New,
+    Gathering,
+    Complete,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<native::RTCIceGatheringState> for IceGatheringState {
+    fn from(value: native::RTCIceGatheringState) -> Self {
+        match value {
+            native::RTCIceGatheringState::Unspecified => Self::Unspecified,
+            native::RTCIceGatheringState::New => Self::New,
+            native::RTCIceGatheringState::Gathering => Self::Gathering,
+            native::RTCIceGatheringState::Complete => Self::Complete,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<native::RTCIceGathererState> for IceGatheringState {
+    fn from(value: native::RTCIceGathererState) -> Self {
+        match value {
+            native::RTCIceGathererState::Unspecified => Self::Unspecified,
+            native::RTCIceGathererState::New => Self::New,
+            native::RTCIceGathererState::Gathering => Self::Gathering,
+            // a closed gatherer will not produce any more candidates
+            native::RTCIceGathererState::Complete | native::RTCIceGathererState::Closed => {
+                Self::Complete
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<wasm::RtcIceGatheringState> for IceGatheringState {
+    fn from(value: wasm::RtcIceGatheringState) -> Self {
+        match value {
+            wasm::RtcIceGatheringState::New => Self::New,
+            wasm::RtcIceGatheringState::Gathering => Self::Gathering,
+            wasm::RtcIceGatheringState::Complete => Self::Complete,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
+/// Same shape as the browser's `RTCIceCandidateInit` JSON.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct IceCandidateInit {
     pub candidate: String,
     pub sdp_mid: Option<String>,
+    #[cfg_attr(feature = "serde", serde(rename = "sdpMLineIndex"))]
     pub sdp_mline_index: Option<u16>,
 }
 
@@ -469,6 +1056,11 @@ impl IceCandidate {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "stats", rename_all = "kebab-case")
+)]
 pub enum StatsReportType {
     CandidatePair(CandidatePairStats),
     LocalCandidate(CandidateStats),
@@ -476,6 +1068,11 @@ pub enum StatsReportType {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum CandidatePairState {
     Unspecified,
     Waiting,
@@ -511,6 +1108,11 @@ impl From<String> for CandidatePairState {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct CandidatePairStats {
     pub id: String,
     pub local_candidate_id: String,
@@ -520,11 +1122,17 @@ pub struct CandidatePairStats {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CandidateType {
+    #[cfg_attr(feature = "serde", serde(rename = "unspecified"))]
     Unspecified,
+    #[cfg_attr(feature = "serde", serde(rename = "host"))]
     Host,
+    #[cfg_attr(feature = "serde", serde(rename = "srflx"))]
     ServerReflexive,
+    #[cfg_attr(feature = "serde", serde(rename = "prflx"))]
     PeerReflexive,
+    #[cfg_attr(feature = "serde", serde(rename = "relay"))]
     Relay,
 }
 
@@ -554,88 +1162,165 @@ impl From<String> for CandidateType {
     }
 }
 
+/// The transport of a candidate. Native peers never gather `Tcp` candidates, as webrtc-rs has
+/// no ICE-TCP, but a browser peer may gather them and announce them to a native one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CandidateProtocol {
+    Unspecified,
+    Udp,
+    Tcp,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<native::NetworkType> for CandidateProtocol {
+    fn from(value: native::NetworkType) -> Self {
+        if value.is_udp() {
+            Self::Udp
+        } else if value.is_tcp() {
+            Self::Tcp
+        } else {
+            Self::Unspecified
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<String> for CandidateProtocol {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "udp" => CandidateProtocol::Udp,
+            "tcp" => CandidateProtocol::Tcp,
+            _ => CandidateProtocol::Unspecified,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct CandidateStats {
     pub id: String,
     pub candidate_type: CandidateType,
+    pub protocol: CandidateProtocol,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OfferOptions {
+    pub ice_restart: bool,
 }
 
 #[derive(Debug)]
 pub struct PeerConnection(
     #[cfg(not(target_arch = "wasm32"))] native::RTCPeerConnection,
     #[cfg(target_arch = "wasm32")] wasm::RtcPeerConnection,
+    privacy::Privacy,
+    /// Set by [`PeerConnection::restart_ice`] so that the next offer restarts ICE.
+    #[cfg(not(target_arch = "wasm32"))]
+    AtomicBool,
 );
 
 impl PeerConnection {
     pub async fn new(configuration: &Configuration) -> Result<Self, Error> {
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            use webrtc::{
-                api::{
-                    interceptor_registry::register_default_interceptors, media_engine::MediaEngine,
-                    APIBuilder,
-                },
-                interceptor::registry::Registry,
-            };
-            let mut media_engine = MediaEngine::default();
-            media_engine.register_default_codecs().unwrap();
-            let mut registry = Registry::new();
-            registry = register_default_interceptors(registry, &mut media_engine).unwrap();
-            let api = APIBuilder::new()
-                .with_media_engine(media_engine)
-                .with_interceptor_registry(registry);
-            let api = api.build();
-            let configuration = native::RTCConfiguration::from(configuration.clone());
-            let peer = api
-                .new_peer_connection(configuration)
-                .await
-                .map_err(|_| Error::FailedToCreatePeer)?;
-            Ok(PeerConnection(peer))
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            let configuration = wasm::RtcConfiguration::from(configuration.clone());
-            Ok(PeerConnection(
-                wasm::RtcPeerConnection::new_with_configuration(&configuration)
-                    .map_err(|_| Error::FailedToCreatePeer)?,
-            ))
-        }
+        Self::new_with_settings(configuration, &NativeSettings::default()).await
+    }
+
+    /// Creates a peer, applying `settings` on native. The settings are ignored in the browser.
+    ///
+    /// Use a [`PeerFactory`] instead when creating many peers.
+    pub async fn new_with_settings(
+        configuration: &Configuration,
+        settings: &NativeSettings,
+    ) -> Result<Self, Error> {
+        PeerFactory::new(configuration, settings)?.create().await
     }
 
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn new_with_api(configuration: &Configuration, api: native::API) -> Result<Self, Error> {
-        let configuration = native::RTCConfiguration::from(configuration.clone());
         let peer = api
-            .new_peer_connection(configuration)
+            .new_peer_connection(native::RTCConfiguration::try_from(configuration.clone())?)
             .await
             .map_err(|_| Error::FailedToCreatePeer)?;
-        Ok(PeerConnection(peer))
+        Ok(PeerConnection(
+            peer,
+            privacy::Privacy::new(configuration.ip_privacy),
+            AtomicBool::new(false),
+        ))
     }
 
     pub async fn create_offer(&self) -> Result<SessionDescription, Error> {
+        self.create_offer_with_options(OfferOptions::default())
+            .await
+    }
+
+    pub async fn create_offer_with_options(
+        &self,
+        options: OfferOptions,
+    ) -> Result<SessionDescription, Error> {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            Ok(SessionDescription(
+            let ice_restart = self.2.swap(false, Ordering::Relaxed) || options.ice_restart;
+            self.1.scrub_created(SessionDescription(
                 self.0
-                    .create_offer(None)
+                    .create_offer(Some(native::RTCOfferOptions {
+                        ice_restart,
+                        ..Default::default()
+                    }))
                     .await
                     .map_err(|_| Error::FailedToCreateOffer)?,
             ))
         }
         #[cfg(target_arch = "wasm32")]
         {
-            Ok(SessionDescription(wasm::RtcSessionDescription::from(
-                wasm::JsFuture::from(self.0.create_offer())
+            let mut offer_options = wasm::RtcOfferOptions::new();
+            offer_options.ice_restart(options.ice_restart);
+            self.1
+                .scrub_created(SessionDescription(wasm::RtcSessionDescription::from(
+                    wasm::JsFuture::from(
+                        self.0.create_offer_with_rtc_offer_options(&offer_options),
+                    )
                     .await
                     .map_err(|_| Error::FailedToCreateOffer)?,
-            )))
+                )))
+        }
+    }
+
+    /// Requests an ICE restart, recovering connectivity after a network change without
+    /// recreating the peer or its data channels. The next offer created restarts ICE.
+    ///
+    /// In browsers this also fires negotiation needed. On native it does not, so the caller
+    /// must create and signal a new offer itself. Browsers without `restartIce` return
+    /// [`Error::Unsupported`], and an offer created with [`OfferOptions::ice_restart`] can be
+    /// used instead.
+    pub fn restart_ice(&self) -> Result<(), Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.2.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            let restart_ice = wasm::Reflect::get(&self.0, &"restartIce".into())
+                .ok()
+                .filter(|restart_ice| restart_ice.is_function())
+                .ok_or(Error::Unsupported("restartIce in this browser"))?
+                .unchecked_into::<js_sys::Function>();
+            restart_ice
+                .call0(&self.0)
+                .map_err(|_| Error::Unsupported("restartIce in this browser"))?;
+            Ok(())
         }
     }
 
     pub async fn create_answer(&self) -> Result<SessionDescription, Error> {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            Ok(SessionDescription(
+            self.1.scrub_created(SessionDescription(
                 self.0
                     .create_answer(None)
                     .await
@@ -644,11 +1329,12 @@ impl PeerConnection {
         }
         #[cfg(target_arch = "wasm32")]
         {
-            Ok(SessionDescription(wasm::RtcSessionDescription::from(
-                wasm::JsFuture::from(self.0.create_answer())
-                    .await
-                    .map_err(|_| Error::FailedToCreateAnswer)?,
-            )))
+            self.1
+                .scrub_created(SessionDescription(wasm::RtcSessionDescription::from(
+                    wasm::JsFuture::from(self.0.create_answer())
+                        .await
+                        .map_err(|_| Error::FailedToCreateAnswer)?,
+                )))
         }
     }
 
@@ -656,6 +1342,7 @@ impl PeerConnection {
         &self,
         session_description: &SessionDescription,
     ) -> Result<(), Error> {
+        let session_description = &self.1.unscrub(session_description);
         #[cfg(not(target_arch = "wasm32"))]
         {
             self.0
@@ -698,6 +1385,194 @@ impl PeerConnection {
         }
     }
 
+    pub async fn local_description(&self) -> Option<SessionDescription> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .local_description()
+                .await
+                .map(SessionDescription)
+                .and_then(|description| self.1.ip_privacy.scrub(description).ok())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.0
+                .local_description()
+                .map(SessionDescription)
+                .and_then(|description| self.1.ip_privacy.scrub(description).ok())
+        }
+    }
+
+    pub async fn current_local_description(&self) -> Option<SessionDescription> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .current_local_description()
+                .await
+                .map(SessionDescription)
+                .and_then(|description| self.1.ip_privacy.scrub(description).ok())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.0
+                .current_local_description()
+                .map(SessionDescription)
+                .and_then(|description| self.1.ip_privacy.scrub(description).ok())
+        }
+    }
+
+    pub async fn pending_local_description(&self) -> Option<SessionDescription> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .pending_local_description()
+                .await
+                .map(SessionDescription)
+                .and_then(|description| self.1.ip_privacy.scrub(description).ok())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.0
+                .pending_local_description()
+                .map(SessionDescription)
+                .and_then(|description| self.1.ip_privacy.scrub(description).ok())
+        }
+    }
+
+    pub async fn remote_description(&self) -> Option<SessionDescription> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0.remote_description().await.map(SessionDescription)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.0.remote_description().map(SessionDescription)
+        }
+    }
+
+    pub async fn current_remote_description(&self) -> Option<SessionDescription> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .current_remote_description()
+                .await
+                .map(SessionDescription)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.0.current_remote_description().map(SessionDescription)
+        }
+    }
+
+    pub async fn pending_remote_description(&self) -> Option<SessionDescription> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .pending_remote_description()
+                .await
+                .map(SessionDescription)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.0.pending_remote_description().map(SessionDescription)
+        }
+    }
+
+    /// The DTLS fingerprint this peer announces, available once a local description is set.
+    pub async fn local_fingerprint(&self) -> Option<sdp::Fingerprint> {
+        let local_description = self.local_description().await?;
+        sdp::Sdp::from_session_description(&local_description)
+            .ok()?
+            .fingerprint()
+    }
+
+    /// The DTLS fingerprint the remote peer announced, available once a remote description is
+    /// set. Comparing it against a known fingerprint pins the remote peer's identity, since
+    /// the DTLS handshake fails if the remote certificate does not match it.
+    pub async fn remote_fingerprint(&self) -> Option<sdp::Fingerprint> {
+        let remote_description = self.remote_description().await?;
+        sdp::Sdp::from_session_description(&remote_description)
+            .ok()?
+            .fingerprint()
+    }
+
+    /// Waits until ICE gathering has finished, so that the local description contains every
+    /// candidate. Returns [`Error::IceGatheringTimedOut`] if `timeout` elapses first.
+    pub async fn wait_for_ice_gathering_complete(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut gathering_complete = self.0.gathering_complete_promise().await;
+            runtime::timeout(timeout, gathering_complete.recv())
+                .await
+                .ok_or(Error::IceGatheringTimedOut)?;
+            Ok(())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use std::cell::RefCell;
+            use wasm_bindgen::JsCast;
+            if self.0.ice_gathering_state() == wasm::RtcIceGatheringState::Complete {
+                return Ok(());
+            }
+            let (sender, receiver) = futures::channel::oneshot::channel::<()>();
+            let sender = RefCell::new(Some(sender));
+            let peer = self.0.clone();
+            let closure = wasm::Closure::wrap(Box::new(move |_event: wasm::JsValue| {
+                if peer.ice_gathering_state() == wasm::RtcIceGatheringState::Complete {
+                    if let Some(sender) = sender.borrow_mut().take() {
+                        _ = sender.send(());
+                    }
+                }
+            }) as Box<dyn Fn(wasm::JsValue)>);
+            self.0
+                .add_event_listener_with_callback(
+                    "icegatheringstatechange",
+                    closure.as_ref().unchecked_ref(),
+                )
+                .unwrap();
+            let result = runtime::timeout(timeout, receiver).await;
+            self.0
+                .remove_event_listener_with_callback(
+                    "icegatheringstatechange",
+                    closure.as_ref().unchecked_ref(),
+                )
+                .unwrap();
+            result.ok_or(Error::IceGatheringTimedOut)?;
+            Ok(())
+        }
+    }
+
+    /// Creates an offer, sets it as the local description and waits for ICE gathering to
+    /// finish, returning a description with every candidate embedded (non-trickle ICE).
+    pub async fn create_offer_complete(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<SessionDescription, Error> {
+        let offer = self.create_offer().await?;
+        self.set_local_description(&offer).await?;
+        self.wait_for_ice_gathering_complete(timeout).await?;
+        self.local_description()
+            .await
+            .ok_or(Error::FailedToCreateOffer)
+    }
+
+    /// Creates an answer, sets it as the local description and waits for ICE gathering to
+    /// finish, returning a description with every candidate embedded (non-trickle ICE).
+    pub async fn create_answer_complete(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<SessionDescription, Error> {
+        let answer = self.create_answer().await?;
+        self.set_local_description(&answer).await?;
+        self.wait_for_ice_gathering_complete(timeout).await?;
+        self.local_description()
+            .await
+            .ok_or(Error::FailedToCreateAnswer)
+    }
+
     pub fn on_connection_state_change(&self, handler: OnPeerConnectionStateChangeFn) {
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -726,24 +1601,163 @@ impl PeerConnection {
         }
     }
 
+    pub fn connection_state(&self) -> PeerConnectionState {
+        PeerConnectionState::from(self.0.connection_state())
+    }
+
+    pub fn signaling_state(&self) -> SignalingState {
+        SignalingState::from(self.0.signaling_state())
+    }
+
+    pub fn ice_connection_state(&self) -> IceConnectionState {
+        IceConnectionState::from(self.0.ice_connection_state())
+    }
+
+    pub fn ice_gathering_state(&self) -> IceGatheringState {
+        IceGatheringState::from(self.0.ice_gathering_state())
+    }
+
+    pub fn on_signaling_state_change(&self, handler: OnSignalingStateChangeFn) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0.on_signaling_state_change(Box::new(move |state| {
+                let future = handler(SignalingState::from(state));
+                Box::pin(async move {
+                    future.await;
+                })
+            }));
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            let peer = self.0.clone();
+            let closure = wasm::Closure::wrap(Box::new(move |_event: wasm::JsValue| {
+                let future = handler(SignalingState::from(peer.signaling_state()));
+                _ = wasm::future_to_promise(async move {
+                    future.await;
+                    Ok(wasm::JsValue::UNDEFINED)
+                });
+            }) as Box<dyn Fn(wasm::JsValue)>);
+            self.0
+                .set_onsignalingstatechange(Some(closure.as_ref().unchecked_ref()));
+            closure.forget();
+        }
+    }
+
+    pub fn on_ice_connection_state_change(&self, handler: OnIceConnectionStateChangeFn) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0
+                .on_ice_connection_state_change(Box::new(move |state| {
+                    let future = handler(IceConnectionState::from(state));
+                    Box::pin(async move {
+                        future.await;
+                    })
+                }));
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            let peer = self.0.clone();
+            let closure = wasm::Closure::wrap(Box::new(move |_event: wasm::JsValue| {
+                let future = handler(IceConnectionState::from(peer.ice_connection_state()));
+                _ = wasm::future_to_promise(async move {
+                    future.await;
+                    Ok(wasm::JsValue::UNDEFINED)
+                });
+            }) as Box<dyn Fn(wasm::JsValue)>);
+            self.0
+                .set_oniceconnectionstatechange(Some(closure.as_ref().unchecked_ref()));
+            closure.forget();
+        }
+    }
+
+    pub fn on_ice_gathering_state_change(&self, handler: OnIceGatheringStateChangeFn) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0.on_ice_gathering_state_change(Box::new(move |state| {
+                let future = handler(IceGatheringState::from(state));
+                Box::pin(async move {
+                    future.await;
+                })
+            }));
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            let peer = self.0.clone();
+            let closure = wasm::Closure::wrap(Box::new(move |_event: wasm::JsValue| {
+                let future = handler(IceGatheringState::from(peer.ice_gathering_state()));
+                _ = wasm::future_to_promise(async move {
+                    future.await;
+                    Ok(wasm::JsValue::UNDEFINED)
+                });
+            }) as Box<dyn Fn(wasm::JsValue)>);
+            self.0
+                .set_onicegatheringstatechange(Some(closure.as_ref().unchecked_ref()));
+            closure.forget();
+        }
+    }
+
+    pub fn on_negotiation_needed(&self, handler: OnNegotiationNeededFn) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.0.on_negotiation_needed(Box::new(move || {
+                let future = handler();
+                Box::pin(async move {
+                    future.await;
+                })
+            }));
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+            let closure = wasm::Closure::wrap(Box::new(move |_event: wasm::JsValue| {
+                let future = handler();
+                _ = wasm::future_to_promise(async move {
+                    future.await;
+                    Ok(wasm::JsValue::UNDEFINED)
+                });
+            }) as Box<dyn Fn(wasm::JsValue)>);
+            self.0
+                .set_onnegotiationneeded(Some(closure.as_ref().unchecked_ref()));
+            closure.forget();
+        }
+    }
+
     pub fn on_ice_candidate(&self, handler: OnIceCandidateFn) {
         #[cfg(not(target_arch = "wasm32"))]
         {
+            let ip_privacy = self.1.ip_privacy;
             self.0.on_ice_candidate(Box::new(move |ice_candidate| {
-                let future =
-                    handler(ice_candidate.map(|ice_candidate| IceCandidate(ice_candidate)));
+                // candidates hidden by `IpPrivacy` are never handed to the handler
+                let future = match ice_candidate {
+                    Some(ice_candidate) => ip_privacy
+                        .scrub_ice_candidate(IceCandidate(ice_candidate))
+                        .map(|ice_candidate| handler(Some(ice_candidate))),
+                    None => Some(handler(None)),
+                };
                 Box::pin(async move {
-                    future.await;
+                    if let Some(future) = future {
+                        future.await;
+                    }
                 })
             }));
         }
         #[cfg(target_arch = "wasm32")]
         {
             use wasm_bindgen::JsCast;
+            let ip_privacy = self.1.ip_privacy;
             let closure = wasm::Closure::wrap(Box::new(move |ice_candidate: wasm::JsValue| {
                 let candidate = wasm::Reflect::get(&ice_candidate, &"candidate".into()).unwrap();
                 let future = if candidate.is_object() {
-                    handler(Some(IceCandidate(wasm::RtcIceCandidate::from(candidate))))
+                    match ip_privacy
+                        .scrub_ice_candidate(IceCandidate(wasm::RtcIceCandidate::from(candidate)))
+                    {
+                        Some(ice_candidate) => handler(Some(ice_candidate)),
+                        // hidden by `IpPrivacy`
+                        None => return,
+                    }
                 } else {
                     handler(None)
                 };
@@ -913,6 +1927,7 @@ impl PeerConnection {
                             StatsReportType::LocalCandidate(CandidateStats {
                                 id: stats.id,
                                 candidate_type: CandidateType::from(stats.candidate_type),
+                                protocol: CandidateProtocol::from(stats.network_type),
                             }),
                         );
                     }
@@ -922,6 +1937,7 @@ impl PeerConnection {
                             StatsReportType::RemoteCandidate(CandidateStats {
                                 id: stats.id,
                                 candidate_type: CandidateType::from(stats.candidate_type),
+                                protocol: CandidateProtocol::from(stats.network_type),
                             }),
                         );
                     }
@@ -991,6 +2007,9 @@ impl PeerConnection {
                             StatsReportType::LocalCandidate(CandidateStats {
                                 id,
                                 candidate_type: CandidateType::from(candidate_type),
+                                protocol: get_string(&stats, "protocol")
+                                    .map(CandidateProtocol::from)
+                                    .unwrap_or(CandidateProtocol::Unspecified),
                             }),
                         );
                     }
@@ -1003,6 +2022,9 @@ impl PeerConnection {
                             StatsReportType::RemoteCandidate(CandidateStats {
                                 id,
                                 candidate_type: CandidateType::from(candidate_type),
+                                protocol: get_string(&stats, "protocol")
+                                    .map(CandidateProtocol::from)
+                                    .unwrap_or(CandidateProtocol::Unspecified),
                             }),
                         );
                     }
@@ -1014,7 +2036,7 @@ impl PeerConnection {
     }
 }
 
-#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum Error {
     /// Failed to create peer.
     #[error("Failed to create peer.")]
@@ -1052,4 +2074,90 @@ pub enum Error {
     /// Failed to get stats.
     #[error("Failed to get stats.")]
     FailedToGetStats,
+    /// ICE gathering timed out.
+    #[error("ICE gathering timed out.")]
+    IceGatheringTimedOut,
+    /// Unexpected SDP type.
+    #[error("Unexpected SDP type.")]
+    UnexpectedSdpType,
+    /// Unexpected signal message.
+    #[error("Unexpected signal message.")]
+    UnexpectedSignalMessage,
+    /// Failed to encode session description.
+    #[error("Failed to encode session description.")]
+    FailedToEncodeSessionDescription,
+    /// Failed to decode session description.
+    #[error("Failed to decode session description.")]
+    FailedToDecodeSessionDescription,
+    /// HTTP request failed.
+    #[error("HTTP request failed.")]
+    HttpRequestFailed,
+    /// Unknown session.
+    #[error("Unknown session.")]
+    UnknownSession,
+    /// Failed to parse SDP.
+    #[error("Failed to parse SDP.")]
+    FailedToParseSdp,
+    /// Failed to generate certificate.
+    #[error("Failed to generate certificate.")]
+    FailedToGenerateCertificate,
+    /// Failed to parse certificate.
+    #[error("Failed to parse certificate.")]
+    FailedToParseCertificate,
+    /// Failed to bind socket.
+    #[error("Failed to bind socket.")]
+    FailedToBindSocket,
+    /// Failed to connect.
+    #[error("Failed to connect.")]
+    FailedToConnect,
+    /// Handshake timed out.
+    #[error("Handshake timed out.")]
+    HandshakeTimedOut,
+    /// Signaling closed.
+    #[error("Signaling closed.")]
+    SignalingClosed,
+    /// Listener closed.
+    #[error("Listener closed.")]
+    ListenerClosed,
+    /// Unknown client.
+    #[error("Unknown client.")]
+    UnknownClient,
+    /// Heartbeat timed out.
+    #[error("Heartbeat timed out.")]
+    HeartbeatTimedOut,
+    /// Reconnect attempts exhausted.
+    #[error("Reconnect attempts exhausted.")]
+    ReconnectAttemptsExhausted,
+    /// Peer closed.
+    #[error("Peer closed.")]
+    PeerClosed,
+    /// Invalid native settings.
+    #[error("Invalid native settings.")]
+    InvalidNativeSettings,
+    /// Invalid configuration, see [`Configuration::validate`].
+    #[error("Invalid configuration: {0}")]
+    InvalidConfiguration(ConfigurationError),
+    /// Invalid TURN credential.
+    #[error("Invalid TURN credential.")]
+    InvalidTurnCredential,
+    /// TURN credential expired.
+    #[error("TURN credential expired.")]
+    TurnCredentialExpired,
+    /// Not supported on this platform.
+    #[error("Not supported on this platform: {0}.")]
+    Unsupported(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigurationError {
+    #[error("ICE server {ice_server} has no URLs.")]
+    MissingUrls { ice_server: usize },
+    #[error("ICE server {ice_server} has an invalid URL {url:?}: {error}")]
+    InvalidUrl {
+        ice_server: usize,
+        url: String,
+        error: ice::IceUrlError,
+    },
+    #[error("ICE server {ice_server} has TURN URL {url:?} but no username or credential.")]
+    MissingCredentials { ice_server: usize, url: String },
 }
@@ -0,0 +1,256 @@
+use std::{future::Future, pin::Pin, sync::Mutex, task::Poll, time::Duration};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    stream::FuturesUnordered,
+    Stream, StreamExt,
+};
+use maybe_sync::dyn_maybe_send;
+
+use crate::{
+    runtime, sdp, DataChannel, Error, PeerConnection, PeerConnectionState, PeerFactory,
+    SessionDescription,
+};
+
+/// Offers received by a signaling source, such as a websocket server or a channel fed by one.
+pub type OfferStream = Pin<Box<dyn_maybe_send!(Stream<Item = IncomingOffer> + 'static)>>;
+
+type Handshake = Pin<Box<dyn_maybe_send!(Future<Output = Option<AcceptedPeer>> + 'static)>>;
+
+/// An offer from a remote peer, and where to send the answer.
+#[derive(Debug)]
+pub struct IncomingOffer {
+    pub offer: SessionDescription,
+    /// Receives the answer, or the error that prevented answering.
+    pub answer_sender: oneshot::Sender<Result<SessionDescription, Error>>,
+}
+
+impl IncomingOffer {
+    /// Wraps `offer`, returning the receiver its answer is sent to.
+    pub fn new(
+        offer: SessionDescription,
+    ) -> (Self, oneshot::Receiver<Result<SessionDescription, Error>>) {
+        let (answer_sender, answer_receiver) = oneshot::channel();
+        (
+            IncomingOffer {
+                offer,
+                answer_sender,
+            },
+            answer_receiver,
+        )
+    }
+}
+
+/// A connected peer returned by [`RtcListener::accept`].
+pub struct AcceptedPeer {
+    pub peer: PeerConnection,
+    /// Data channels opened by the remote peer before it was accepted, which are all open.
+    pub data_channels: Vec<AcceptedDataChannel>,
+    /// Data channels the remote peer opened after these, as soon as they are announced and
+    /// whether open yet or not. Registering [`PeerConnection::on_data_channel`] stops feeding
+    /// this receiver.
+    pub later_data_channels: mpsc::UnboundedReceiver<AcceptedDataChannel>,
+}
+
+/// A data channel opened by an accepted peer.
+pub struct AcceptedDataChannel {
+    pub data_channel: DataChannel,
+    /// Messages received on the channel, including those that arrived before it was handed
+    /// out. Registering [`DataChannel::on_message`] stops feeding this receiver.
+    pub messages: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+/// Accepts peers like a socket listener: every incoming offer is answered with a new peer,
+/// and peers are returned by [`RtcListener::accept`] once connected and their initial data
+/// channels are open.
+///
+/// Answers are non-trickle, so the signaling source only needs to relay one offer and one
+/// answer per peer. Peers that fail or time out are closed and never returned. Handshakes
+/// make progress while [`RtcListener::accept`] is awaited.
+pub struct RtcListener {
+    factory: PeerFactory,
+    offers: OfferStream,
+    offers_ended: bool,
+    handshake_timeout: Option<Duration>,
+    handshakes: FuturesUnordered<Handshake>,
+}
+
+impl RtcListener {
+    pub fn new(factory: PeerFactory, offers: OfferStream) -> Self {
+        RtcListener {
+            factory,
+            offers,
+            offers_ended: false,
+            handshake_timeout: Some(Duration::from_secs(10)),
+            handshakes: FuturesUnordered::new(),
+        }
+    }
+
+    /// How long a peer has to connect after its offer is received, 10 seconds by default.
+    pub fn with_handshake_timeout(mut self, handshake_timeout: Option<Duration>) -> Self {
+        self.handshake_timeout = handshake_timeout;
+        self
+    }
+
+    /// Waits for the next connected peer. Returns [`Error::ListenerClosed`] once the offer
+    /// stream has ended and every pending handshake has finished.
+    pub async fn accept(&mut self) -> Result<AcceptedPeer, Error> {
+        futures::future::poll_fn(|cx| loop {
+            if !self.offers_ended {
+                match self.offers.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(incoming_offer)) => {
+                        self.handshakes.push(Box::pin(handshake(
+                            self.factory.clone(),
+                            incoming_offer,
+                            self.handshake_timeout,
+                        )));
+                        continue;
+                    }
+                    Poll::Ready(None) => self.offers_ended = true,
+                    Poll::Pending => {}
+                }
+            }
+            match self.handshakes.poll_next_unpin(cx) {
+                Poll::Ready(Some(Some(accepted_peer))) => return Poll::Ready(Ok(accepted_peer)),
+                Poll::Ready(Some(None)) => continue,
+                Poll::Ready(None) if self.offers_ended => {
+                    return Poll::Ready(Err(Error::ListenerClosed))
+                }
+                _ => return Poll::Pending,
+            }
+        })
+        .await
+    }
+}
+
+impl std::fmt::Debug for RtcListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RtcListener")
+            .field("offers_ended", &self.offers_ended)
+            .field("handshake_timeout", &self.handshake_timeout)
+            .field("handshakes", &self.handshakes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+async fn handshake(
+    factory: PeerFactory,
+    incoming_offer: IncomingOffer,
+    timeout: Option<Duration>,
+) -> Option<AcceptedPeer> {
+    let IncomingOffer {
+        offer,
+        answer_sender,
+    } = incoming_offer;
+    let peer = match factory.create().await {
+        Ok(peer) => peer,
+        Err(err) => {
+            _ = answer_sender.send(Err(err));
+            return None;
+        }
+    };
+    let (state_sender, mut states) = mpsc::unbounded();
+    peer.on_connection_state_change(Box::new(move |state| {
+        _ = state_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+    // every channel is sent on both, so the two receivers stay in step
+    let (data_channel_sender, mut data_channels) = mpsc::unbounded();
+    let (open_sender, mut opens) = mpsc::unbounded();
+    peer.on_data_channel(Box::new(move |data_channel| {
+        // registered right away, as the channel may open and receive messages before it is
+        // handed out
+        let (opened_sender, opened) = oneshot::channel();
+        let opened_sender = Mutex::new(Some(opened_sender));
+        data_channel.on_open(Box::new(move || {
+            if let Some(opened_sender) = opened_sender.lock().unwrap().take() {
+                _ = opened_sender.send(());
+            }
+            Box::pin(async {})
+        }));
+        let (message_sender, messages) = mpsc::unbounded();
+        data_channel.on_message(Box::new(move |message, _| {
+            _ = message_sender.unbounded_send(message);
+            Box::pin(async {})
+        }));
+        _ = data_channel_sender.unbounded_send(AcceptedDataChannel {
+            data_channel,
+            messages,
+        });
+        _ = open_sender.unbounded_send(opened);
+        Box::pin(async {})
+    }));
+
+    let mut answer_sender = Some(answer_sender);
+    let result = runtime::timeout(
+        timeout,
+        establish(
+            &peer,
+            &offer,
+            &mut answer_sender,
+            &mut states,
+            &mut data_channels,
+            &mut opens,
+        ),
+    )
+    .await
+    .unwrap_or(Err(Error::HandshakeTimedOut));
+    match result {
+        Ok(initial_data_channels) => Some(AcceptedPeer {
+            peer,
+            data_channels: initial_data_channels,
+            later_data_channels: data_channels,
+        }),
+        Err(err) => {
+            if let Some(answer_sender) = answer_sender {
+                _ = answer_sender.send(Err(err));
+            }
+            _ = peer.close().await;
+            None
+        }
+    }
+}
+
+async fn establish(
+    peer: &PeerConnection,
+    offer: &SessionDescription,
+    answer_sender: &mut Option<oneshot::Sender<Result<SessionDescription, Error>>>,
+    states: &mut mpsc::UnboundedReceiver<PeerConnectionState>,
+    data_channels: &mut mpsc::UnboundedReceiver<AcceptedDataChannel>,
+    opens: &mut mpsc::UnboundedReceiver<oneshot::Receiver<()>>,
+) -> Result<Vec<AcceptedDataChannel>, Error> {
+    let offers_data_channels = sdp::Sdp::from_session_description(offer)?
+        .media
+        .iter()
+        .any(|media| media.description.media == "application");
+    peer.set_remote_description(offer).await?;
+    let answer = peer.create_answer_complete(None).await?;
+    if let Some(answer_sender) = answer_sender.take() {
+        answer_sender
+            .send(Ok(answer))
+            .map_err(|_| Error::SignalingClosed)?;
+    }
+    loop {
+        match states.next().await {
+            Some(PeerConnectionState::Connected) => break,
+            Some(PeerConnectionState::Failed | PeerConnectionState::Closed) | None => {
+                return Err(Error::FailedToConnect);
+            }
+            Some(_) => {}
+        }
+    }
+    // channels announced later stay queued in `data_channels`, which is handed out with the
+    // peer, so none are lost
+    let mut initial_data_channels = vec![];
+    if offers_data_channels {
+        initial_data_channels.push(data_channels.next().await.ok_or(Error::FailedToConnect)?);
+        while let Ok(data_channel) = data_channels.try_recv() {
+            initial_data_channels.push(data_channel);
+        }
+    }
+    for _ in &initial_data_channels {
+        let opened = opens.next().await.ok_or(Error::FailedToConnect)?;
+        opened.await.map_err(|_| Error::FailedToConnect)?;
+    }
+    Ok(initial_data_channels)
+}
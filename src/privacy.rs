@@ -0,0 +1,314 @@
+use std::{net::IpAddr, sync::Mutex};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::native;
+#[cfg(target_arch = "wasm32")]
+use crate::wasm;
+use crate::{sdp, CandidateType, Error, IceCandidate, SessionDescription};
+
+/// Hides this peer's IP addresses from the remote peer and the signaling channel.
+///
+/// Candidates that would reveal an address are dropped from [`PeerConnection::on_ice_candidate`]
+/// and from local descriptions, and related addresses are replaced with `0.0.0.0`. Host
+/// candidates are still available under mDNS names, which native peers then gather unless
+/// mDNS is disabled in the [`NativeSettings`](crate::NativeSettings).
+///
+/// [`PeerConnection::on_ice_candidate`]: crate::PeerConnection::on_ice_candidate
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IpPrivacy {
+    #[default]
+    Disabled,
+    /// Hides local network addresses by dropping host candidates with an IP address.
+    HideLocal,
+    /// Also hides the public address by dropping server and peer reflexive candidates, leaving
+    /// only mDNS host candidates and relay candidates. Unlike
+    /// [`IceTransportPolicy::Relay`](crate::IceTransportPolicy::Relay), peers on the same
+    /// network can still connect directly.
+    HideLocalAndPublic,
+}
+
+impl IpPrivacy {
+    pub(crate) fn allows(self, candidate_type: CandidateType, address: &str) -> bool {
+        let has_ip_address = address.parse::<IpAddr>().is_ok();
+        match self {
+            IpPrivacy::Disabled => true,
+            IpPrivacy::HideLocal => match candidate_type {
+                CandidateType::ServerReflexive
+                | CandidateType::PeerReflexive
+                | CandidateType::Relay => true,
+                CandidateType::Host | CandidateType::Unspecified => !has_ip_address,
+            },
+            IpPrivacy::HideLocalAndPublic => match candidate_type {
+                CandidateType::Relay => true,
+                CandidateType::Host => !has_ip_address,
+                _ => false,
+            },
+        }
+    }
+
+    /// Returns the candidate to signal in place of `candidate`, if any.
+    pub(crate) fn scrub_candidate(self, mut candidate: sdp::Candidate) -> Option<sdp::Candidate> {
+        if !self.allows(candidate.candidate_type, &candidate.address) {
+            return None;
+        }
+        if self != IpPrivacy::Disabled && candidate.related_address.is_some() {
+            candidate.related_address = Some("0.0.0.0".to_owned());
+            candidate.related_port = Some(0);
+        }
+        Some(candidate)
+    }
+
+    /// Returns the gathered candidate to hand to `on_ice_candidate` in place of `ice_candidate`,
+    /// if any.
+    pub(crate) fn scrub_ice_candidate(self, ice_candidate: IceCandidate) -> Option<IceCandidate> {
+        if self == IpPrivacy::Disabled {
+            return Some(ice_candidate);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut ice_candidate = ice_candidate.0;
+            let candidate_type = match ice_candidate.typ {
+                native::RTCIceCandidateType::Unspecified => CandidateType::Unspecified,
+                native::RTCIceCandidateType::Host => CandidateType::Host,
+                native::RTCIceCandidateType::Srflx => CandidateType::ServerReflexive,
+                native::RTCIceCandidateType::Prflx => CandidateType::PeerReflexive,
+                native::RTCIceCandidateType::Relay => CandidateType::Relay,
+            };
+            if !self.allows(candidate_type, &ice_candidate.address) {
+                return None;
+            }
+            if !ice_candidate.related_address.is_empty() {
+                ice_candidate.related_address = "0.0.0.0".to_owned();
+                ice_candidate.related_port = 0;
+            }
+            Some(IceCandidate(ice_candidate))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let init = ice_candidate.to_init().ok()?;
+            let candidate = self.scrub_candidate(init.candidate.parse().ok()?)?;
+            let mut scrubbed_init = wasm::RtcIceCandidateInit::new(&candidate.to_string());
+            scrubbed_init.sdp_mid(init.sdp_mid.as_deref());
+            scrubbed_init.sdp_m_line_index(init.sdp_mline_index);
+            Some(IceCandidate(
+                wasm::RtcIceCandidate::new(&scrubbed_init).ok()?,
+            ))
+        }
+    }
+
+    /// Drops and scrubs candidates, and clears the addresses of `c=` lines, which browsers set
+    /// to the address of the default candidate.
+    pub(crate) fn scrub(
+        self,
+        description: SessionDescription,
+    ) -> Result<SessionDescription, Error> {
+        if self == IpPrivacy::Disabled {
+            return Ok(description);
+        }
+        let mut sdp = sdp::Sdp::from_session_description(&description)?;
+        scrub_connection_lines(&mut sdp.lines);
+        for media in &mut sdp.media {
+            scrub_connection_lines(&mut media.lines);
+            if media.description.port != 0 {
+                media.description.port = 9;
+            }
+            if media.attribute("rtcp").is_some() {
+                media.set_attribute("rtcp", Some("9 IN IP4 0.0.0.0"));
+            }
+            media.attributes = std::mem::take(&mut media.attributes)
+                .into_iter()
+                .filter_map(|mut attribute| {
+                    if attribute.name != "candidate" {
+                        return Some(attribute);
+                    }
+                    // candidates that cannot be parsed are dropped, rather than risk leaking them
+                    let candidate = attribute.value.as_deref()?.parse().ok()?;
+                    let candidate = self.scrub_candidate(candidate)?.to_string();
+                    attribute.value = candidate.strip_prefix("candidate:").map(str::to_owned);
+                    Some(attribute)
+                })
+                .collect();
+        }
+        sdp.to_session_description(description.sdp_type())
+    }
+}
+
+fn scrub_connection_lines(lines: &mut [sdp::Line]) {
+    for line in lines.iter_mut().filter(|line| line.kind == 'c') {
+        line.value = if line.value.contains("IP6") {
+            "IN IP6 ::".to_owned()
+        } else {
+            "IN IP4 0.0.0.0".to_owned()
+        };
+    }
+}
+
+/// Per-peer privacy state.
+///
+/// Native peers only accept the exact SDP they created as a local description, so the
+/// scrubbed copy of the last created offer or answer is remembered and swapped back for the
+/// original when it is set.
+#[derive(Debug)]
+pub(crate) struct Privacy {
+    pub(crate) ip_privacy: IpPrivacy,
+    last_created: Mutex<Option<(String, SessionDescription)>>,
+}
+
+impl Privacy {
+    pub(crate) fn new(ip_privacy: IpPrivacy) -> Self {
+        Privacy {
+            ip_privacy,
+            last_created: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn scrub_created(
+        &self,
+        description: SessionDescription,
+    ) -> Result<SessionDescription, Error> {
+        if self.ip_privacy == IpPrivacy::Disabled {
+            return Ok(description);
+        }
+        let scrubbed = self.ip_privacy.scrub(description.clone())?;
+        *self.last_created.lock().unwrap() = Some((scrubbed.sdp(), description));
+        Ok(scrubbed)
+    }
+
+    /// The description to actually set locally in place of `description`.
+    pub(crate) fn unscrub(&self, description: &SessionDescription) -> SessionDescription {
+        match &*self.last_created.lock().unwrap() {
+            Some((scrubbed, original))
+                if *scrubbed == description.sdp()
+                    && original.sdp_type() == description.sdp_type() =>
+            {
+                original.clone()
+            }
+            _ => description.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SdpType;
+
+    const OFFER: &str = "\
+        v=0\r\n\
+        o=- 4215775240449105457 2 IN IP4 127.0.0.1\r\n\
+        s=-\r\n\
+        t=0 0\r\n\
+        a=group:BUNDLE 0\r\n\
+        m=application 54321 UDP/DTLS/SCTP webrtc-datachannel\r\n\
+        c=IN IP4 203.0.113.7\r\n\
+        a=rtcp:54321 IN IP4 203.0.113.7\r\n\
+        a=candidate:1 1 udp 2122260223 192.168.1.20 54321 typ host\r\n\
+        a=candidate:2 1 udp 2122260223 4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local 54321 typ host\r\n\
+        a=candidate:3 1 udp 1686052607 203.0.113.7 54321 typ srflx raddr 192.168.1.20 rport 54321\r\n\
+        a=candidate:4 1 udp 41885439 198.51.100.3 3478 typ relay raddr 203.0.113.7 rport 54321\r\n\
+        a=candidate:garbage\r\n\
+        a=ice-ufrag:EsAw\r\n\
+        a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+        a=fingerprint:sha-256 0B:30:55:7A:9F:C4:E9:0E:33:58:7D:A2:C7:EC:11:36:5B:80:A5:CA:EF:14:39:5E:83:A8:CD:F2:17:3C:61:86\r\n\
+        a=setup:actpass\r\n\
+        a=mid:0\r\n\
+        a=sctp-port:5000\r\n";
+
+    fn candidate(line: &str) -> sdp::Candidate {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn allows_candidates_by_type() {
+        use CandidateType::*;
+        let ip = "192.168.1.20";
+        let mdns = "4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local";
+        for candidate_type in [Host, ServerReflexive, PeerReflexive, Relay, Unspecified] {
+            assert!(IpPrivacy::Disabled.allows(candidate_type, ip));
+        }
+        assert!(!IpPrivacy::HideLocal.allows(Host, ip));
+        assert!(IpPrivacy::HideLocal.allows(Host, mdns));
+        assert!(!IpPrivacy::HideLocal.allows(Unspecified, ip));
+        assert!(IpPrivacy::HideLocal.allows(ServerReflexive, ip));
+        assert!(IpPrivacy::HideLocal.allows(PeerReflexive, ip));
+        assert!(IpPrivacy::HideLocal.allows(Relay, ip));
+        assert!(!IpPrivacy::HideLocalAndPublic.allows(Host, ip));
+        assert!(IpPrivacy::HideLocalAndPublic.allows(Host, mdns));
+        assert!(!IpPrivacy::HideLocalAndPublic.allows(ServerReflexive, ip));
+        assert!(!IpPrivacy::HideLocalAndPublic.allows(PeerReflexive, ip));
+        assert!(IpPrivacy::HideLocalAndPublic.allows(Relay, ip));
+    }
+
+    #[test]
+    fn scrubs_related_addresses() {
+        let srflx = "candidate:3 1 udp 1686052607 203.0.113.7 54321 typ srflx raddr 192.168.1.20 rport 54321";
+        let scrubbed = IpPrivacy::HideLocal
+            .scrub_candidate(candidate(srflx))
+            .unwrap();
+        assert_eq!(scrubbed.address, "203.0.113.7");
+        assert_eq!(scrubbed.related_address.as_deref(), Some("0.0.0.0"));
+        assert_eq!(scrubbed.related_port, Some(0));
+        assert!(!scrubbed.to_string().contains("192.168.1.20"));
+
+        assert_eq!(
+            IpPrivacy::Disabled.scrub_candidate(candidate(srflx)),
+            Some(candidate(srflx))
+        );
+        assert_eq!(
+            IpPrivacy::HideLocalAndPublic.scrub_candidate(candidate(srflx)),
+            None
+        );
+        let host = "candidate:1 1 udp 2122260223 192.168.1.20 54321 typ host";
+        assert_eq!(IpPrivacy::HideLocal.scrub_candidate(candidate(host)), None);
+    }
+
+    #[test]
+    fn scrubs_descriptions() {
+        let description = SessionDescription::new(SdpType::Offer, OFFER).unwrap();
+        let scrubbed = IpPrivacy::HideLocalAndPublic
+            .scrub(description)
+            .unwrap()
+            .sdp();
+        for ip in ["192.168.1.20", "203.0.113.7"] {
+            assert!(!scrubbed.contains(ip), "{} in {}", ip, scrubbed);
+        }
+        // the address of the relay candidate is not the peer's own
+        assert!(scrubbed.contains("198.51.100.3 3478 typ relay raddr 0.0.0.0 rport 0"));
+        assert!(scrubbed.contains("4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local"));
+        assert!(!scrubbed.contains("garbage"));
+        assert!(scrubbed.contains("m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n"));
+        assert!(scrubbed.contains("c=IN IP4 0.0.0.0\r\n"));
+        assert!(scrubbed.contains("a=rtcp:9 IN IP4 0.0.0.0\r\n"));
+        assert!(scrubbed.contains("a=ice-ufrag:EsAw\r\n"));
+
+        let scrubbed = IpPrivacy::HideLocal
+            .scrub(SessionDescription::new(SdpType::Offer, OFFER).unwrap())
+            .unwrap()
+            .sdp();
+        assert!(!scrubbed.contains("192.168.1.20"));
+        assert!(scrubbed.contains("203.0.113.7 54321 typ srflx raddr 0.0.0.0 rport 0"));
+    }
+
+    #[test]
+    fn keeps_descriptions_when_disabled() {
+        let description = SessionDescription::new(SdpType::Offer, OFFER).unwrap();
+        assert_eq!(IpPrivacy::Disabled.scrub(description).unwrap().sdp(), OFFER);
+    }
+
+    #[test]
+    fn unscrubs_the_last_created_description() {
+        let privacy = Privacy::new(IpPrivacy::HideLocal);
+        let original = SessionDescription::new(SdpType::Offer, OFFER).unwrap();
+        let scrubbed = privacy.scrub_created(original).unwrap();
+        assert_ne!(scrubbed.sdp(), OFFER);
+        assert_eq!(privacy.unscrub(&scrubbed).sdp(), OFFER);
+
+        // the same SDP as another type, or any other SDP, is set as is
+        let answer = SessionDescription::new(SdpType::Answer, &scrubbed.sdp()).unwrap();
+        assert_eq!(privacy.unscrub(&answer).sdp(), scrubbed.sdp());
+        let other =
+            SessionDescription::new(SdpType::Offer, &OFFER.replace("EsAw", "Xy12")).unwrap();
+        assert!(privacy.unscrub(&other).sdp().contains("Xy12"));
+    }
+}
@@ -0,0 +1,569 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use futures::{
+    channel::mpsc,
+    future::{self, Either},
+    lock::Mutex as AsyncMutex,
+    StreamExt,
+};
+use maybe_sync::{dyn_maybe_send, dyn_maybe_send_sync};
+
+use crate::{
+    runtime, DataChannel, DataChannelInit, Error, PeerConnection, PeerConnectionState, PeerFactory,
+    SessionDescription,
+};
+
+/// Sends an offer over a signaling transport, resolving to the remote peer's answer.
+pub type SendOfferFn = Box<
+    dyn_maybe_send_sync!(
+        (Fn(
+            SessionDescription,
+        ) -> Pin<
+            Box<dyn_maybe_send!(Future<Output = Result<SessionDescription, Error>> + 'static)>,
+        >)
+    ),
+>;
+
+/// Exponential backoff between reconnect attempts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Backoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// How much the delay grows after every failed attempt.
+    pub multiplier: f64,
+    /// The fraction of each delay, from `0.0` to `1.0`, that is randomly taken off, so that
+    /// clients dropped at the same time do not all reconnect at the same time.
+    pub jitter: f64,
+    /// Gives up after this many failed attempts in a row. `None` retries forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.,
+            jitter: 0.5,
+            max_attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// The delay before reconnect attempt `attempt`, counting from 1. Never more than
+    /// `max_delay`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.jittered_delay(attempt, runtime::random())
+    }
+
+    /// The delay with `random` in `0.0..1.0` picking the jitter.
+    fn jittered_delay(&self, attempt: u32, random: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        // delays that overflow a `Duration`, or are not a number at all, are capped
+        let delay = Duration::try_from_secs_f64(delay)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0., 1.) * random;
+        Duration::try_from_secs_f64(delay.as_secs_f64() * (1. - jitter))
+            .unwrap_or(delay)
+            .min(delay)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectEvent {
+    /// The connection failed, or stayed disconnected for longer than the disconnect timeout.
+    /// Reconnecting starts with the next event.
+    Disconnected,
+    /// Waiting `delay` before reconnect attempt `attempt`.
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+    },
+    ReconnectFailed {
+        attempt: u32,
+        error: Error,
+    },
+    /// Connected again, and the data channels are open.
+    Reconnected {
+        attempts: u32,
+    },
+    Message {
+        label: String,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Connected,
+    /// Still connected, but the connection has been disconnected since `since`, as told by
+    /// [`runtime::now`], and may recover on its own.
+    Interrupted {
+        since: Duration,
+    },
+    Backoff {
+        attempt: u32,
+    },
+    Attempt {
+        attempt: u32,
+        delay: Duration,
+    },
+    Exhausted,
+    Closed,
+}
+
+enum Internal {
+    State {
+        generation: u64,
+        state: PeerConnectionState,
+    },
+    Message {
+        label: String,
+        data: Vec<u8>,
+    },
+    Closed,
+}
+
+enum Setup {
+    State(PeerConnectionState),
+    Open,
+}
+
+struct Connection {
+    peer: PeerConnection,
+    data_channels: HashMap<String, DataChannel>,
+}
+
+/// A peer that is closed if dropped before being taken, such as when
+/// [`ReconnectingPeer::next_event`] is dropped during an attempt.
+struct PendingPeer(Option<PeerConnection>);
+
+impl PendingPeer {
+    fn peer(&self) -> &PeerConnection {
+        self.0.as_ref().unwrap()
+    }
+
+    fn take(mut self) -> PeerConnection {
+        self.0.take().unwrap()
+    }
+}
+
+impl Drop for PendingPeer {
+    fn drop(&mut self) {
+        if let Some(peer) = self.0.take() {
+            runtime::spawn(async move {
+                _ = peer.close().await;
+            });
+        }
+    }
+}
+
+/// A peer that negotiates again when its connection fails.
+///
+/// This peer makes the offer, passing it to a [`SendOfferFn`] that returns the answer over any
+/// signaling transport. Every connection is a new [`PeerConnection`] with the same data
+/// channels, which are recreated on reconnect, so messages are received as
+/// [`ReconnectEvent::Message`] rather than through handlers on the channels.
+///
+/// Reconnecting happens while [`ReconnectingPeer::next_event`] is awaited.
+pub struct ReconnectingPeer {
+    factory: PeerFactory,
+    send_offer: SendOfferFn,
+    data_channels: Vec<(String, DataChannelInit)>,
+    backoff: Backoff,
+    connect_timeout: Option<Duration>,
+    disconnect_timeout: Option<Duration>,
+    connection: Mutex<Option<Connection>>,
+    phase: Mutex<Phase>,
+    generation: AtomicU64,
+    events: AsyncMutex<mpsc::UnboundedReceiver<Internal>>,
+    event_sender: mpsc::UnboundedSender<Internal>,
+}
+
+impl ReconnectingPeer {
+    pub fn new(factory: PeerFactory, send_offer: SendOfferFn) -> Self {
+        let (event_sender, events) = mpsc::unbounded();
+        ReconnectingPeer {
+            factory,
+            send_offer,
+            data_channels: vec![],
+            backoff: Backoff::default(),
+            connect_timeout: Some(Duration::from_secs(10)),
+            disconnect_timeout: Some(Duration::from_secs(5)),
+            connection: Mutex::new(None),
+            phase: Mutex::new(Phase::Closed),
+            generation: AtomicU64::new(0),
+            events: AsyncMutex::new(events),
+            event_sender,
+        }
+    }
+
+    /// Adds a data channel, created on every connection.
+    pub fn with_data_channel(mut self, label: impl Into<String>, options: DataChannelInit) -> Self {
+        self.data_channels.push((label.into(), options));
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// How long each attempt has to connect and open its data channels, 10 seconds by default.
+    pub fn with_connect_timeout(mut self, connect_timeout: Option<Duration>) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// How long the connection may stay disconnected before reconnecting, 5 seconds by
+    /// default. With `None`, only a failed connection is reconnected.
+    pub fn with_disconnect_timeout(mut self, disconnect_timeout: Option<Duration>) -> Self {
+        self.disconnect_timeout = disconnect_timeout;
+        self
+    }
+
+    /// Makes the first connection. It is not retried, so a peer that never connected does not
+    /// keep retrying a bad configuration.
+    pub async fn connect(self) -> Result<Self, Error> {
+        let connection = self.establish().await?;
+        *self.connection.lock().unwrap() = Some(connection);
+        *self.phase.lock().unwrap() = Phase::Connected;
+        Ok(self)
+    }
+
+    /// Waits for the next event. Returns [`Error::ReconnectAttemptsExhausted`] once
+    /// [`Backoff::max_attempts`] attempts in a row have failed, and [`Error::PeerClosed`] once
+    /// [`ReconnectingPeer::close`] is called.
+    ///
+    /// Dropping the returned future during an attempt abandons it and closes its peer, and the
+    /// attempt is made again by the next call.
+    pub async fn next_event(&self) -> Result<ReconnectEvent, Error> {
+        let mut events = self.events.lock().await;
+        loop {
+            let phase = *self.phase.lock().unwrap();
+            match phase {
+                Phase::Connected | Phase::Interrupted { .. } => {
+                    let event = match (phase, self.disconnect_timeout) {
+                        (Phase::Interrupted { since }, Some(disconnect_timeout)) => {
+                            let remaining =
+                                (since + disconnect_timeout).saturating_sub(runtime::now());
+                            let timer = pin!(runtime::sleep(remaining));
+                            match future::select(events.next(), timer).await {
+                                Either::Left((event, _)) => event,
+                                Either::Right(_) => match self.disconnect(phase).await {
+                                    Some(event) => return Ok(event),
+                                    None => continue,
+                                },
+                            }
+                        }
+                        _ => events.next().await,
+                    };
+                    match event {
+                        Some(Internal::State { generation, state })
+                            if generation == self.generation.load(Ordering::Relaxed) =>
+                        {
+                            match state {
+                                PeerConnectionState::Failed => {
+                                    if let Some(event) = self.disconnect(phase).await {
+                                        return Ok(event);
+                                    }
+                                }
+                                PeerConnectionState::Disconnected if phase == Phase::Connected => {
+                                    self.transition(
+                                        phase,
+                                        Phase::Interrupted {
+                                            since: runtime::now(),
+                                        },
+                                    );
+                                }
+                                PeerConnectionState::Connected => {
+                                    self.transition(phase, Phase::Connected);
+                                }
+                                _ => {}
+                            }
+                        }
+                        Some(Internal::Message { label, data }) => {
+                            return Ok(ReconnectEvent::Message { label, data });
+                        }
+                        // states of earlier connections, and wakeups from `close`
+                        Some(Internal::State { .. } | Internal::Closed) | None => {}
+                    }
+                }
+                Phase::Backoff { attempt } => {
+                    if self
+                        .backoff
+                        .max_attempts
+                        .is_some_and(|max_attempts| attempt > max_attempts)
+                    {
+                        self.set_phase(Phase::Exhausted);
+                        continue;
+                    }
+                    let delay = self.backoff.delay(attempt);
+                    self.set_phase(Phase::Attempt { attempt, delay });
+                    return Ok(ReconnectEvent::Reconnecting { attempt, delay });
+                }
+                Phase::Attempt { attempt, delay } => {
+                    runtime::sleep(delay).await;
+                    return match self.establish().await {
+                        Ok(connection) => {
+                            *self.connection.lock().unwrap() = Some(connection);
+                            // `close` may have been called during the attempt
+                            if !self.transition(phase, Phase::Connected) {
+                                let connection = self.connection.lock().unwrap().take();
+                                if let Some(connection) = connection {
+                                    _ = connection.peer.close().await;
+                                }
+                                continue;
+                            }
+                            Ok(ReconnectEvent::Reconnected { attempts: attempt })
+                        }
+                        Err(error) => {
+                            self.transition(
+                                phase,
+                                Phase::Backoff {
+                                    attempt: attempt + 1,
+                                },
+                            );
+                            Ok(ReconnectEvent::ReconnectFailed { attempt, error })
+                        }
+                    };
+                }
+                Phase::Exhausted => return Err(Error::ReconnectAttemptsExhausted),
+                Phase::Closed => return Err(Error::PeerClosed),
+            }
+        }
+    }
+
+    /// Whether connected, which is not the case while the connection is disconnected or
+    /// reconnecting.
+    pub fn is_connected(&self) -> bool {
+        *self.phase.lock().unwrap() == Phase::Connected
+    }
+
+    /// The current connection's data channel with this label, if connected.
+    pub fn data_channel(&self, label: &str) -> Option<DataChannel> {
+        self.connection
+            .lock()
+            .unwrap()
+            .as_ref()?
+            .data_channels
+            .get(label)
+            .cloned()
+    }
+
+    /// Sends `data` on the data channel with this label. Fails while reconnecting.
+    pub async fn send(&self, label: &str, data: &[u8]) -> Result<(), Error> {
+        self.data_channel(label)
+            .ok_or(Error::FailedToSend)?
+            .send(data)
+            .await
+    }
+
+    /// Closes the connection and stops reconnecting.
+    pub async fn close(&self) -> Result<(), Error> {
+        self.set_phase(Phase::Closed);
+        _ = self.event_sender.unbounded_send(Internal::Closed);
+        let connection = self.connection.lock().unwrap().take();
+        match connection {
+            Some(connection) => connection.peer.close().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Closes the connection that failed in phase `from` and starts reconnecting, unless the
+    /// phase was changed in the meantime.
+    async fn disconnect(&self, from: Phase) -> Option<ReconnectEvent> {
+        if !self.transition(from, Phase::Backoff { attempt: 1 }) {
+            return None;
+        }
+        let connection = self.connection.lock().unwrap().take();
+        if let Some(connection) = connection {
+            _ = connection.peer.close().await;
+        }
+        Some(ReconnectEvent::Disconnected)
+    }
+
+    fn set_phase(&self, phase: Phase) {
+        *self.phase.lock().unwrap() = phase;
+    }
+
+    /// Moves from phase `from` to `to`, unless the phase was changed in the meantime.
+    fn transition(&self, from: Phase, to: Phase) -> bool {
+        let mut phase = self.phase.lock().unwrap();
+        if *phase != from {
+            return false;
+        }
+        *phase = to;
+        true
+    }
+
+    async fn establish(&self) -> Result<Connection, Error> {
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let peer = PendingPeer(Some(self.factory.create().await?));
+        let result = runtime::timeout(
+            self.connect_timeout,
+            self.negotiate(peer.peer(), generation),
+        )
+        .await
+        .unwrap_or(Err(Error::HandshakeTimedOut));
+        match result {
+            Ok(data_channels) => Ok(Connection {
+                peer: peer.take(),
+                data_channels,
+            }),
+            Err(err) => {
+                _ = peer.take().close().await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn negotiate(
+        &self,
+        peer: &PeerConnection,
+        generation: u64,
+    ) -> Result<HashMap<String, DataChannel>, Error> {
+        let (setup_sender, mut setup) = mpsc::unbounded();
+        {
+            let setup_sender = setup_sender.clone();
+            let event_sender = self.event_sender.clone();
+            peer.on_connection_state_change(Box::new(move |state| {
+                _ = event_sender.unbounded_send(Internal::State { generation, state });
+                _ = setup_sender.unbounded_send(Setup::State(state));
+                Box::pin(async {})
+            }));
+        }
+        let mut data_channels = HashMap::new();
+        for (label, options) in &self.data_channels {
+            let data_channel = peer.create_data_channel(label, options.clone()).await?;
+            let setup_sender = setup_sender.clone();
+            data_channel.on_open(Box::new(move || {
+                _ = setup_sender.unbounded_send(Setup::Open);
+                Box::pin(async {})
+            }));
+            let event_sender = self.event_sender.clone();
+            let message_label = label.clone();
+            data_channel.on_message(Box::new(move |data, _| {
+                _ = event_sender.unbounded_send(Internal::Message {
+                    label: message_label.clone(),
+                    data,
+                });
+                Box::pin(async {})
+            }));
+            data_channels.insert(label.clone(), data_channel);
+        }
+        drop(setup_sender);
+
+        let offer = peer.create_offer_complete(None).await?;
+        let answer = (self.send_offer)(offer).await?;
+        peer.set_remote_description(&answer).await?;
+
+        let mut connected = false;
+        let mut opened = 0;
+        while !connected || opened < data_channels.len() {
+            match setup.next().await {
+                Some(Setup::State(PeerConnectionState::Connected)) => connected = true,
+                Some(Setup::State(PeerConnectionState::Failed | PeerConnectionState::Closed))
+                | None => return Err(Error::FailedToConnect),
+                Some(Setup::State(_)) => {}
+                Some(Setup::Open) => opened += 1,
+            }
+        }
+        Ok(data_channels)
+    }
+}
+
+impl std::fmt::Debug for ReconnectingPeer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectingPeer")
+            .field("data_channels", &self.data_channels)
+            .field("backoff", &self.backoff)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("phase", &*self.phase.lock().unwrap())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn without_jitter() -> Backoff {
+        Backoff {
+            jitter: 0.,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn delays_grow_up_to_the_maximum() {
+        let backoff = without_jitter();
+        let delays = (1..=9)
+            .map(|attempt| backoff.delay(attempt).as_secs_f64())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, [0.5, 1., 2., 4., 8., 16., 30., 30., 30.]);
+        assert_eq!(backoff.delay(0), backoff.delay(1));
+        assert_eq!(backoff.delay(u32::MAX), backoff.max_delay);
+    }
+
+    #[test]
+    fn jitter_takes_off_a_fraction() {
+        let backoff = Backoff::default();
+        assert_eq!(backoff.jittered_delay(2, 0.), Duration::from_secs(1));
+        assert_eq!(backoff.jittered_delay(2, 0.5), Duration::from_millis(750));
+        assert_eq!(backoff.jittered_delay(2, 1.), Duration::from_millis(500));
+        for attempt in 1..20 {
+            let delay = backoff.delay(attempt);
+            let max = without_jitter().delay(attempt);
+            assert!(delay <= max && delay >= max / 2, "{:?}", delay);
+        }
+        // out of range jitter is clamped
+        let backoff = Backoff {
+            jitter: 2.,
+            ..Default::default()
+        };
+        assert_eq!(backoff.jittered_delay(2, 1.), Duration::ZERO);
+    }
+
+    #[test]
+    fn extreme_settings_do_not_panic() {
+        let backoff = Backoff {
+            max_delay: Duration::MAX,
+            ..without_jitter()
+        };
+        assert_eq!(backoff.delay(u32::MAX), Duration::MAX);
+        assert_eq!(backoff.delay(2000), Duration::MAX);
+        assert_eq!(backoff.delay(3), Duration::from_secs(2));
+
+        let backoff = Backoff {
+            initial_delay: Duration::ZERO,
+            multiplier: f64::INFINITY,
+            ..Default::default()
+        };
+        assert!(backoff.delay(3) <= backoff.max_delay);
+        let backoff = Backoff {
+            multiplier: f64::NAN,
+            jitter: f64::NAN,
+            ..Default::default()
+        };
+        assert!(backoff.delay(3) <= backoff.max_delay);
+        let backoff = Backoff {
+            multiplier: -2.,
+            ..without_jitter()
+        };
+        assert!(backoff.delay(2) <= backoff.max_delay);
+    }
+}
@@ -0,0 +1,109 @@
+use std::{future::Future, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_name = setTimeout)]
+        pub fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+
+        #[wasm_bindgen(js_namespace = performance, js_name = now)]
+        pub fn performance_now() -> f64;
+    }
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::time::sleep(duration).await;
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            wasm::set_timeout(&resolve, millis);
+        });
+        _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+    }
+}
+
+/// Runs `future` in the background, for cleanup that cannot be awaited, such as in `Drop`.
+/// On native, nothing runs outside of a Tokio runtime.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        handle.spawn(future);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn spawn(future: impl Future<Output = ()> + 'static) {
+    wasm_bindgen_futures::spawn_local(future);
+}
+
+/// Time since the Unix epoch. `SystemTime` is unavailable on wasm, so `Date.now()` is used there.
+pub(crate) fn unix_time() -> Duration {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Duration::from_millis(js_sys::Date::now() as u64)
+    }
+}
+
+/// Monotonic time since an unspecified point, for measuring intervals.
+pub(crate) fn now() -> Duration {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        START.get_or_init(std::time::Instant::now).elapsed()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Duration::from_secs_f64(wasm::performance_now() / 1000.)
+    }
+}
+
+/// A random number in `0.0..1.0`, for jitter rather than anything secure.
+pub(crate) fn random() -> f64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::hash::{BuildHasher, Hasher};
+        // every `RandomState` is seeded differently, which is enough without a dependency
+        let bits = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Math::random()
+    }
+}
+
+/// Runs `future` to completion, or returns `None` if `duration` elapses first.
+pub(crate) async fn timeout<F: Future>(duration: Option<Duration>, future: F) -> Option<F::Output> {
+    let Some(duration) = duration else {
+        return Some(future.await);
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        tokio::time::timeout(duration, future).await.ok()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        use futures::future::{select, Either};
+        let future = std::pin::pin!(future);
+        let timer = std::pin::pin!(sleep(duration));
+        match select(future, timer).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    }
+}
@@ -0,0 +1,909 @@
+//! Structured SDP parsing and munging.
+//!
+//! [`Sdp`] splits a description into its session section and media sections, keeping every
+//! line so that serializing an unmodified [`Sdp`] gives back an equivalent description. Within
+//! each section, attributes are written after the other lines, as RFC 8866 orders them, so
+//! well-formed descriptions keep their line order. Line endings are normalized to CRLF and
+//! blank lines are dropped. Typed views such as [`Candidate`] and [`Fingerprint`] are parsed
+//! from attributes on demand.
+
+use std::{fmt, str::FromStr};
+
+use crate::{CandidateType, Error, SdpType, SessionDescription};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sdp {
+    /// Session-level lines other than attributes (`v=`, `o=`, `s=`, `t=`, ...).
+    pub lines: Vec<Line>,
+    /// Session-level attributes.
+    pub attributes: Vec<Attribute>,
+    pub media: Vec<MediaSection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaSection {
+    pub description: MediaDescription,
+    /// Media-level lines other than the `m=` line and attributes (`c=`, `b=`, ...).
+    pub lines: Vec<Line>,
+    pub attributes: Vec<Attribute>,
+}
+
+/// The value of an `m=` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaDescription {
+    pub media: String,
+    pub port: u16,
+    pub port_count: Option<u16>,
+    pub protocol: String,
+    pub formats: Vec<String>,
+}
+
+/// A line of the form `<kind>=<value>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub kind: char,
+    pub value: String,
+}
+
+/// An `a=<name>` or `a=<name>:<value>` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub foundation: String,
+    pub component: u16,
+    pub protocol: String,
+    pub priority: u32,
+    pub address: String,
+    pub port: u16,
+    pub candidate_type: CandidateType,
+    pub related_address: Option<String>,
+    pub related_port: Option<u16>,
+    pub tcp_type: Option<String>,
+    /// Remaining `key value` pairs, such as `generation 0`. A trailing key without a value is
+    /// kept with `None`.
+    pub extensions: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub algorithm: String,
+    pub value: Vec<u8>,
+}
+
+impl Sdp {
+    pub fn parse(sdp: &str) -> Result<Self, Error> {
+        let mut parsed = Sdp {
+            lines: vec![],
+            attributes: vec![],
+            media: vec![],
+        };
+        for line in sdp.lines() {
+            if line.is_empty() {
+                continue;
+            }
+            let Line { kind, value } = line.parse::<Line>()?;
+            match kind {
+                'm' => parsed.media.push(MediaSection {
+                    description: value.parse()?,
+                    lines: vec![],
+                    attributes: vec![],
+                }),
+                'a' => {
+                    let attribute = value.parse::<Attribute>()?;
+                    match parsed.media.last_mut() {
+                        Some(media) => media.attributes.push(attribute),
+                        None => parsed.attributes.push(attribute),
+                    }
+                }
+                _ => {
+                    let line = Line { kind, value };
+                    match parsed.media.last_mut() {
+                        Some(media) => media.lines.push(line),
+                        None => parsed.lines.push(line),
+                    }
+                }
+            }
+        }
+        if parsed.lines.first().map(|line| line.kind) != Some('v') {
+            return Err(Error::FailedToParseSdp);
+        }
+        Ok(parsed)
+    }
+
+    pub fn from_session_description(
+        session_description: &SessionDescription,
+    ) -> Result<Self, Error> {
+        Self::parse(&session_description.sdp())
+    }
+
+    pub fn to_session_description(&self, sdp_type: SdpType) -> Result<SessionDescription, Error> {
+        SessionDescription::new(sdp_type, &self.to_string())
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+
+    pub fn set_attribute(&mut self, name: &str, value: Option<&str>) {
+        set_attribute(&mut self.attributes, name, value);
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) {
+        self.attributes.retain(|attribute| attribute.name != name);
+    }
+
+    /// The DTLS fingerprint, taken from the first media section or from the session.
+    pub fn fingerprint(&self) -> Option<Fingerprint> {
+        self.media
+            .iter()
+            .find_map(|media| media.attribute("fingerprint"))
+            .or_else(|| self.attribute("fingerprint"))
+            .and_then(|fingerprint| fingerprint.parse().ok())
+    }
+
+    /// The ICE username fragment, taken from the first media section or from the session.
+    pub fn ice_ufrag(&self) -> Option<&str> {
+        self.media
+            .iter()
+            .find_map(|media| media.attribute("ice-ufrag"))
+            .or_else(|| self.attribute("ice-ufrag"))
+    }
+
+    /// The ICE password, taken from the first media section or from the session.
+    pub fn ice_pwd(&self) -> Option<&str> {
+        self.media
+            .iter()
+            .find_map(|media| media.attribute("ice-pwd"))
+            .or_else(|| self.attribute("ice-pwd"))
+    }
+
+    /// Every candidate of every media section.
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.media
+            .iter()
+            .flat_map(|media| media.candidates())
+            .collect()
+    }
+
+    /// Removes the candidates of every media section for which `keep` returns `false`.
+    pub fn retain_candidates(&mut self, mut keep: impl FnMut(&Candidate) -> bool) {
+        for media in &mut self.media {
+            media.retain_candidates(&mut keep);
+        }
+    }
+}
+
+impl FromStr for Sdp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Sdp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            write!(f, "{}\r\n", line)?;
+        }
+        for attribute in &self.attributes {
+            write!(f, "a={}\r\n", attribute)?;
+        }
+        for media in &self.media {
+            write!(f, "{}", media)?;
+        }
+        Ok(())
+    }
+}
+
+impl MediaSection {
+    pub fn mid(&self) -> Option<&str> {
+        self.attribute("mid")
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+
+    pub fn set_attribute(&mut self, name: &str, value: Option<&str>) {
+        set_attribute(&mut self.attributes, name, value);
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) {
+        self.attributes.retain(|attribute| attribute.name != name);
+    }
+
+    pub fn max_message_size(&self) -> Option<u32> {
+        self.attribute("max-message-size")
+            .and_then(|max_message_size| max_message_size.parse().ok())
+    }
+
+    pub fn set_max_message_size(&mut self, max_message_size: u32) {
+        self.set_attribute("max-message-size", Some(&max_message_size.to_string()));
+    }
+
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.attributes
+            .iter()
+            .filter(|attribute| attribute.name == "candidate")
+            .filter_map(|attribute| attribute.value.as_deref()?.parse().ok())
+            .collect()
+    }
+
+    /// Removes the candidates for which `keep` returns `false`. Candidates that cannot be
+    /// parsed are kept.
+    pub fn retain_candidates(&mut self, mut keep: impl FnMut(&Candidate) -> bool) {
+        self.attributes.retain(|attribute| {
+            if attribute.name != "candidate" {
+                return true;
+            }
+            match attribute.value.as_deref().map(Candidate::from_str) {
+                Some(Ok(candidate)) => keep(&candidate),
+                _ => true,
+            }
+        });
+    }
+}
+
+impl fmt::Display for MediaSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m={}\r\n", self.description)?;
+        for line in &self.lines {
+            write!(f, "{}\r\n", line)?;
+        }
+        for attribute in &self.attributes {
+            write!(f, "a={}\r\n", attribute)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MediaDescription {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(' ');
+        let (Some(media), Some(port), Some(protocol)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(Error::FailedToParseSdp);
+        };
+        let (port, port_count) = match port.split_once('/') {
+            Some((port, port_count)) => (
+                port.parse().map_err(|_| Error::FailedToParseSdp)?,
+                Some(port_count.parse().map_err(|_| Error::FailedToParseSdp)?),
+            ),
+            None => (port.parse().map_err(|_| Error::FailedToParseSdp)?, None),
+        };
+        Ok(MediaDescription {
+            media: media.to_owned(),
+            port,
+            port_count,
+            protocol: protocol.to_owned(),
+            formats: fields.map(|format| format.to_owned()).collect(),
+        })
+    }
+}
+
+impl fmt::Display for MediaDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.media, self.port)?;
+        if let Some(port_count) = self.port_count {
+            write!(f, "/{}", port_count)?;
+        }
+        write!(f, " {}", self.protocol)?;
+        for format in &self.formats {
+            write!(f, " {}", format)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Line {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(kind), Some('=')) if kind.is_ascii_alphabetic() => Ok(Line {
+                kind,
+                value: chars.as_str().to_owned(),
+            }),
+            _ => Err(Error::FailedToParseSdp),
+        }
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.kind, self.value)
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match s.split_once(':') {
+            Some((name, value)) => (name, Some(value.to_owned())),
+            None => (s, None),
+        };
+        if name.is_empty() {
+            return Err(Error::FailedToParseSdp);
+        }
+        Ok(Attribute {
+            name: name.to_owned(),
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}:{}", self.name, value),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl Candidate {
+    /// Whether this candidate reveals an IP address (as opposed to an mDNS hostname).
+    pub fn has_ip_address(&self) -> bool {
+        self.address.parse::<std::net::IpAddr>().is_ok()
+    }
+}
+
+impl FromStr for Candidate {
+    type Err = Error;
+
+    /// Parses a candidate with or without its `a=candidate:` or `candidate:` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("a=").unwrap_or(s);
+        let s = s.strip_prefix("candidate:").unwrap_or(s);
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        if fields.len() < 8 || fields[6] != "typ" {
+            return Err(Error::FailedToParseSdp);
+        }
+        let candidate_type = match fields[7] {
+            "host" => CandidateType::Host,
+            "srflx" => CandidateType::ServerReflexive,
+            "prflx" => CandidateType::PeerReflexive,
+            "relay" => CandidateType::Relay,
+            _ => return Err(Error::FailedToParseSdp),
+        };
+        let mut candidate = Candidate {
+            foundation: fields[0].to_owned(),
+            component: fields[1].parse().map_err(|_| Error::FailedToParseSdp)?,
+            protocol: fields[2].to_owned(),
+            priority: fields[3].parse().map_err(|_| Error::FailedToParseSdp)?,
+            address: fields[4].to_owned(),
+            port: fields[5].parse().map_err(|_| Error::FailedToParseSdp)?,
+            candidate_type,
+            related_address: None,
+            related_port: None,
+            tcp_type: None,
+            extensions: vec![],
+        };
+        for pair in fields[8..].chunks(2) {
+            match *pair {
+                ["raddr", value] => candidate.related_address = Some(value.to_owned()),
+                ["rport", value] => {
+                    candidate.related_port =
+                        Some(value.parse().map_err(|_| Error::FailedToParseSdp)?);
+                }
+                ["tcptype", value] => candidate.tcp_type = Some(value.to_owned()),
+                [key, value] => candidate
+                    .extensions
+                    .push((key.to_owned(), Some(value.to_owned()))),
+                [key] => candidate.extensions.push((key.to_owned(), None)),
+                _ => unreachable!(),
+            }
+        }
+        Ok(candidate)
+    }
+}
+
+impl fmt::Display for Candidate {
+    /// Formats the candidate with its `candidate:` prefix, as used in SDP and in
+    /// [`IceCandidateInit`](crate::IceCandidateInit).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "candidate:{} {} {} {} {} {} typ {}",
+            self.foundation,
+            self.component,
+            self.protocol,
+            self.priority,
+            self.address,
+            self.port,
+            match self.candidate_type {
+                CandidateType::Host | CandidateType::Unspecified => "host",
+                CandidateType::ServerReflexive => "srflx",
+                CandidateType::PeerReflexive => "prflx",
+                CandidateType::Relay => "relay",
+            }
+        )?;
+        if let Some(related_address) = &self.related_address {
+            write!(f, " raddr {}", related_address)?;
+        }
+        if let Some(related_port) = self.related_port {
+            write!(f, " rport {}", related_port)?;
+        }
+        if let Some(tcp_type) = &self.tcp_type {
+            write!(f, " tcptype {}", tcp_type)?;
+        }
+        for (key, value) in &self.extensions {
+            write!(f, " {}", key)?;
+            if let Some(value) = value {
+                write!(f, " {}", value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = Error;
+
+    /// Parses the value of a `fingerprint` attribute, such as `sha-256 AB:CD:...`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, value) = s.trim().split_once(' ').ok_or(Error::FailedToParseSdp)?;
+        let value = value
+            .trim()
+            .split(':')
+            .map(|byte| u8::from_str_radix(byte, 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Error::FailedToParseSdp)?;
+        Ok(Fingerprint {
+            algorithm: algorithm.to_ascii_lowercase(),
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.algorithm)?;
+        for (index, byte) in self.value.iter().enumerate() {
+            if index > 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn find_attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .map(|attribute| attribute.value.as_deref().unwrap_or(""))
+}
+
+fn set_attribute(attributes: &mut Vec<Attribute>, name: &str, value: Option<&str>) {
+    let value = value.map(|value| value.to_owned());
+    match attributes
+        .iter_mut()
+        .find(|attribute| attribute.name == name)
+    {
+        Some(attribute) => attribute.value = value,
+        None => attributes.push(Attribute {
+            name: name.to_owned(),
+            value,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // offers as each implementation generates them, the browsers' with a microphone, a camera
+    // and a data channel
+    const CHROME: &str = "\
+        v=0\r\n\
+        o=- 4215775240449105457 2 IN IP4 127.0.0.1\r\n\
+        s=-\r\n\
+        t=0 0\r\n\
+        a=group:BUNDLE 0 1 2\r\n\
+        a=extmap-allow-mixed\r\n\
+        a=msid-semantic: WMS 6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b\r\n\
+        m=audio 9 UDP/TLS/RTP/SAVPF 111 63 9 0 8 13 110 126\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=rtcp:9 IN IP4 0.0.0.0\r\n\
+        a=candidate:3348127260 1 udp 2122260223 192.168.1.20 54321 typ host generation 0 network-id 1 network-cost 10\r\n\
+        a=candidate:1467250027 1 tcp 1518280447 192.168.1.20 9 typ host tcptype active generation 0 network-id 1 network-cost 10\r\n\
+        a=candidate:842163049 1 udp 1686052607 203.0.113.7 54321 typ srflx raddr 192.168.1.20 rport 54321 generation 0 network-id 1 network-cost 10\r\n\
+        a=ice-ufrag:EsAw\r\n\
+        a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+        a=ice-options:trickle\r\n\
+        a=fingerprint:sha-256 4E:6B:2A:91:0C:D3:7F:58:E2:19:A4:3B:C6:8D:05:F1:72:9E:B8:34:0A:DF:61:C5:13:87:EA:4C:29:B0:76:F3\r\n\
+        a=setup:actpass\r\n\
+        a=mid:0\r\n\
+        a=extmap:1 urn:ietf:params:rtp-hdrext:ssrc-audio-level\r\n\
+        a=extmap:2 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time\r\n\
+        a=extmap:3 http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01\r\n\
+        a=extmap:4 urn:ietf:params:rtp-hdrext:sdes:mid\r\n\
+        a=sendrecv\r\n\
+        a=msid:6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b 0a1b2c3d-4e5f-4a6b-8c7d-9e0f1a2b3c4d\r\n\
+        a=rtcp-mux\r\n\
+        a=rtcp-rsize\r\n\
+        a=rtpmap:111 opus/48000/2\r\n\
+        a=rtcp-fb:111 transport-cc\r\n\
+        a=fmtp:111 minptime=10;useinbandfec=1\r\n\
+        a=rtpmap:63 red/48000/2\r\n\
+        a=fmtp:63 111/111\r\n\
+        a=rtpmap:9 G722/8000\r\n\
+        a=rtpmap:0 PCMU/8000\r\n\
+        a=rtpmap:8 PCMA/8000\r\n\
+        a=rtpmap:13 CN/8000\r\n\
+        a=rtpmap:110 telephone-event/48000\r\n\
+        a=rtpmap:126 telephone-event/8000\r\n\
+        a=ssrc:1601187447 cname:k3JQ2a9Xx5mUe0rT\r\n\
+        a=ssrc:1601187447 msid:6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b 0a1b2c3d-4e5f-4a6b-8c7d-9e0f1a2b3c4d\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 96 97 45 46\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=rtcp:9 IN IP4 0.0.0.0\r\n\
+        a=ice-ufrag:EsAw\r\n\
+        a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+        a=ice-options:trickle\r\n\
+        a=fingerprint:sha-256 4E:6B:2A:91:0C:D3:7F:58:E2:19:A4:3B:C6:8D:05:F1:72:9E:B8:34:0A:DF:61:C5:13:87:EA:4C:29:B0:76:F3\r\n\
+        a=setup:actpass\r\n\
+        a=mid:1\r\n\
+        a=extmap:14 urn:ietf:params:rtp-hdrext:toffset\r\n\
+        a=extmap:2 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time\r\n\
+        a=extmap:13 urn:3gpp:video-orientation\r\n\
+        a=extmap:3 http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01\r\n\
+        a=extmap:5 http://www.webrtc.org/experiments/rtp-hdrext/playout-delay\r\n\
+        a=extmap:6 http://www.webrtc.org/experiments/rtp-hdrext/video-content-type\r\n\
+        a=extmap:7 http://www.webrtc.org/experiments/rtp-hdrext/video-timing\r\n\
+        a=extmap:8 http://www.webrtc.org/experiments/rtp-hdrext/color-space\r\n\
+        a=extmap:4 urn:ietf:params:rtp-hdrext:sdes:mid\r\n\
+        a=extmap:10 urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id\r\n\
+        a=extmap:11 urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id\r\n\
+        a=sendrecv\r\n\
+        a=msid:6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b b7e2c9d4-51a3-4f86-a0d2-3c8e7f1b6a95\r\n\
+        a=rtcp-mux\r\n\
+        a=rtcp-rsize\r\n\
+        a=rtpmap:96 VP8/90000\r\n\
+        a=rtcp-fb:96 goog-remb\r\n\
+        a=rtcp-fb:96 transport-cc\r\n\
+        a=rtcp-fb:96 ccm fir\r\n\
+        a=rtcp-fb:96 nack\r\n\
+        a=rtcp-fb:96 nack pli\r\n\
+        a=rtpmap:97 rtx/90000\r\n\
+        a=fmtp:97 apt=96\r\n\
+        a=rtpmap:45 AV1/90000\r\n\
+        a=rtcp-fb:45 goog-remb\r\n\
+        a=rtcp-fb:45 transport-cc\r\n\
+        a=rtcp-fb:45 ccm fir\r\n\
+        a=rtcp-fb:45 nack\r\n\
+        a=rtcp-fb:45 nack pli\r\n\
+        a=fmtp:45 level-idx=5;profile=0;tier=0\r\n\
+        a=rtpmap:46 rtx/90000\r\n\
+        a=fmtp:46 apt=45\r\n\
+        a=ssrc-group:FID 2366439402 3826853296\r\n\
+        a=ssrc:2366439402 cname:k3JQ2a9Xx5mUe0rT\r\n\
+        a=ssrc:2366439402 msid:6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b b7e2c9d4-51a3-4f86-a0d2-3c8e7f1b6a95\r\n\
+        a=ssrc:3826853296 cname:k3JQ2a9Xx5mUe0rT\r\n\
+        a=ssrc:3826853296 msid:6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b b7e2c9d4-51a3-4f86-a0d2-3c8e7f1b6a95\r\n\
+        m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=ice-ufrag:EsAw\r\n\
+        a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r\n\
+        a=ice-options:trickle\r\n\
+        a=fingerprint:sha-256 4E:6B:2A:91:0C:D3:7F:58:E2:19:A4:3B:C6:8D:05:F1:72:9E:B8:34:0A:DF:61:C5:13:87:EA:4C:29:B0:76:F3\r\n\
+        a=setup:actpass\r\n\
+        a=mid:2\r\n\
+        a=sctp-port:5000\r\n\
+        a=max-message-size:262144\r\n";
+
+    const FIREFOX: &str = "\
+        v=0\r\n\
+        o=mozilla...THIS_IS_SDPARTA-125.0.3 7253497366478484236 0 IN IP4 0.0.0.0\r\n\
+        s=-\r\n\
+        t=0 0\r\n\
+        a=sendrecv\r\n\
+        a=fingerprint:sha-256 C1:5A:E8:03:9D:74:2F:B6:48:0E:D7:91:6C:A3:3E:58:F2:07:BB:64:1D:C9:85:4A:E0:2B:97:73:16:AD:5F:C8\r\n\
+        a=group:BUNDLE 0 1 2\r\n\
+        a=ice-options:trickle\r\n\
+        a=msid-semantic:WMS *\r\n\
+        m=audio 9 UDP/TLS/RTP/SAVPF 109 9 0 8 101\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=candidate:0 1 UDP 2122252543 4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local 50000 typ host\r\n\
+        a=candidate:2 1 TCP 2105524479 4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local 9 typ host tcptype active\r\n\
+        a=candidate:1 1 UDP 1686052863 203.0.113.7 50000 typ srflx raddr 0.0.0.0 rport 0\r\n\
+        a=sendrecv\r\n\
+        a=end-of-candidates\r\n\
+        a=extmap:1 urn:ietf:params:rtp-hdrext:ssrc-audio-level\r\n\
+        a=extmap:2/recvonly urn:ietf:params:rtp-hdrext:csrc-audio-level\r\n\
+        a=extmap:3 urn:ietf:params:rtp-hdrext:sdes:mid\r\n\
+        a=fmtp:109 maxplaybackrate=48000;stereo=1;useinbandfec=1\r\n\
+        a=fmtp:101 0-15\r\n\
+        a=ice-pwd:b4ac7e1f9bd0d8a5b9a2c4e8f1a3d5c7\r\n\
+        a=ice-ufrag:3c9a1f2e\r\n\
+        a=mid:0\r\n\
+        a=msid:{3a9f6c1e-2b7d-4e80-9c54-d1a6e8f02b37} {8c3e0a52-6f1b-4d97-a2e4-5b0c9d7f3e16}\r\n\
+        a=rtcp-mux\r\n\
+        a=rtpmap:109 opus/48000/2\r\n\
+        a=rtpmap:9 G722/8000/1\r\n\
+        a=rtpmap:0 PCMU/8000\r\n\
+        a=rtpmap:8 PCMA/8000\r\n\
+        a=rtpmap:101 telephone-event/8000\r\n\
+        a=setup:actpass\r\n\
+        a=ssrc:3014426584 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        m=video 9 UDP/TLS/RTP/SAVPF 120 124 121 125\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=sendrecv\r\n\
+        a=extmap:3 urn:ietf:params:rtp-hdrext:sdes:mid\r\n\
+        a=extmap:4 http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time\r\n\
+        a=extmap:5 urn:ietf:params:rtp-hdrext:toffset\r\n\
+        a=extmap:6/recvonly http://www.webrtc.org/experiments/rtp-hdrext/playout-delay\r\n\
+        a=extmap:7 http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01\r\n\
+        a=extmap:8 urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id\r\n\
+        a=extmap:9 urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id\r\n\
+        a=fmtp:120 max-fs=12288;max-fr=60\r\n\
+        a=fmtp:124 apt=120\r\n\
+        a=fmtp:121 max-fs=12288;max-fr=60\r\n\
+        a=fmtp:125 apt=121\r\n\
+        a=ice-pwd:b4ac7e1f9bd0d8a5b9a2c4e8f1a3d5c7\r\n\
+        a=ice-ufrag:3c9a1f2e\r\n\
+        a=mid:1\r\n\
+        a=msid:{3a9f6c1e-2b7d-4e80-9c54-d1a6e8f02b37} {e41d7b28-93c5-4a0f-8e6d-2f7a1c5b9d03}\r\n\
+        a=rid:h send\r\n\
+        a=rid:m send\r\n\
+        a=rid:l send\r\n\
+        a=rtcp-fb:120 nack\r\n\
+        a=rtcp-fb:120 nack pli\r\n\
+        a=rtcp-fb:120 ccm fir\r\n\
+        a=rtcp-fb:120 goog-remb\r\n\
+        a=rtcp-fb:120 transport-cc\r\n\
+        a=rtcp-fb:121 nack\r\n\
+        a=rtcp-fb:121 nack pli\r\n\
+        a=rtcp-fb:121 ccm fir\r\n\
+        a=rtcp-fb:121 goog-remb\r\n\
+        a=rtcp-fb:121 transport-cc\r\n\
+        a=rtcp-mux\r\n\
+        a=rtcp-rsize\r\n\
+        a=rtpmap:120 VP8/90000\r\n\
+        a=rtpmap:124 rtx/90000\r\n\
+        a=rtpmap:121 VP9/90000\r\n\
+        a=rtpmap:125 rtx/90000\r\n\
+        a=setup:actpass\r\n\
+        a=simulcast:send h;m;l\r\n\
+        a=ssrc:1154036120 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        a=ssrc:2895307661 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        a=ssrc:3601892754 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        a=ssrc:720461839 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        a=ssrc:4087325516 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        a=ssrc:1932748205 cname:{5f1d2c8a-7e3b-4a69-b0d4-9c6e1f8a2b57}\r\n\
+        a=ssrc-group:FID 1154036120 2895307661\r\n\
+        a=ssrc-group:FID 3601892754 720461839\r\n\
+        a=ssrc-group:FID 4087325516 1932748205\r\n\
+        m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=sendrecv\r\n\
+        a=ice-pwd:b4ac7e1f9bd0d8a5b9a2c4e8f1a3d5c7\r\n\
+        a=ice-ufrag:3c9a1f2e\r\n\
+        a=mid:2\r\n\
+        a=setup:actpass\r\n\
+        a=sctp-port:5000\r\n\
+        a=max-message-size:1073741823\r\n";
+
+    const WEBRTC_RS: &str = "\
+        v=0\r\n\
+        o=- 8393329487254839163 715823590 IN IP4 0.0.0.0\r\n\
+        s=-\r\n\
+        t=0 0\r\n\
+        a=fingerprint:sha-256 0B:30:55:7A:9F:C4:E9:0E:33:58:7D:A2:C7:EC:11:36:5B:80:A5:CA:EF:14:39:5E:83:A8:CD:F2:17:3C:61:86\r\n\
+        a=extmap-allow-mixed\r\n\
+        a=group:BUNDLE 0\r\n\
+        m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n\
+        c=IN IP4 0.0.0.0\r\n\
+        a=setup:actpass\r\n\
+        a=mid:0\r\n\
+        a=sendrecv\r\n\
+        a=sctp-port:5000\r\n\
+        a=ice-ufrag:UvnRkmTmYXgrdEDk\r\n\
+        a=ice-pwd:uYMfQTRmUOkqwTkxhTlIuSDbsbFEnWCu\r\n\
+        a=candidate:167090039 1 udp 2130706431 192.168.1.20 50000 typ host\r\n\
+        a=candidate:167090039 2 udp 2130706431 192.168.1.20 50000 typ host\r\n\
+        a=candidate:3546328463 1 udp 1694498815 203.0.113.7 50001 typ srflx raddr 0.0.0.0 rport 50001\r\n\
+        a=end-of-candidates\r\n";
+
+    const CHROME_FINGERPRINT: &str = "4E:6B:2A:91:0C:D3:7F:58:E2:19:A4:3B:C6:8D:05:F1:72:9E:B8:34:0A:DF:61:C5:13:87:EA:4C:29:B0:76:F3";
+    const FIREFOX_FINGERPRINT: &str = "C1:5A:E8:03:9D:74:2F:B6:48:0E:D7:91:6C:A3:3E:58:F2:07:BB:64:1D:C9:85:4A:E0:2B:97:73:16:AD:5F:C8";
+    const WEBRTC_RS_FINGERPRINT: &str = "0B:30:55:7A:9F:C4:E9:0E:33:58:7D:A2:C7:EC:11:36:5B:80:A5:CA:EF:14:39:5E:83:A8:CD:F2:17:3C:61:86";
+
+    #[test]
+    fn fixtures_round_trip() {
+        for (fixture, expected_fingerprint) in [
+            (CHROME, CHROME_FINGERPRINT),
+            (FIREFOX, FIREFOX_FINGERPRINT),
+            (WEBRTC_RS, WEBRTC_RS_FINGERPRINT),
+        ] {
+            let sdp = Sdp::parse(fixture).unwrap();
+            assert_eq!(sdp.to_string(), fixture);
+            let application = sdp.media.last().unwrap();
+            assert_eq!(application.description.media, "application");
+            assert_eq!(application.description.protocol, "UDP/DTLS/SCTP");
+            assert_eq!(application.description.formats, ["webrtc-datachannel"]);
+            let fingerprint = sdp.fingerprint().unwrap();
+            assert_eq!(fingerprint.algorithm, "sha-256");
+            assert_eq!(
+                fingerprint.to_string(),
+                format!("sha-256 {}", expected_fingerprint)
+            );
+            for candidate in sdp.candidates() {
+                let line = format!("a={}", candidate);
+                assert!(fixture.contains(&line), "{}", line);
+            }
+        }
+    }
+
+    #[test]
+    fn chrome_offer() {
+        let sdp = Sdp::parse(CHROME).unwrap();
+        assert_eq!(
+            sdp.attribute("msid-semantic"),
+            Some(" WMS 6f4b2a1e-8c3d-4e5f-9a7b-1c2d3e4f5a6b")
+        );
+        assert_eq!(sdp.attribute("group"), Some("BUNDLE 0 1 2"));
+        assert_eq!(
+            sdp.media.iter().map(MediaSection::mid).collect::<Vec<_>>(),
+            [Some("0"), Some("1"), Some("2")]
+        );
+        assert_eq!(sdp.ice_ufrag(), Some("EsAw"));
+        assert_eq!(sdp.ice_pwd(), Some("P2uYro0UCOQ4zxjKXaWCBui1"));
+        let video = &sdp.media[1];
+        assert_eq!(video.description.formats, ["96", "97", "45", "46"]);
+        assert_eq!(
+            video
+                .attributes
+                .iter()
+                .filter(|attribute| attribute.name == "extmap")
+                .count(),
+            11
+        );
+        assert_eq!(
+            video.attribute("ssrc-group"),
+            Some("FID 2366439402 3826853296")
+        );
+        assert_eq!(sdp.media[2].max_message_size(), Some(262144));
+        // candidates are only in the first of the bundled sections
+        assert!(sdp.media[1..]
+            .iter()
+            .all(|media| media.candidates().is_empty()));
+        let candidates = sdp.candidates();
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[1].tcp_type.as_deref(), Some("active"));
+        assert_eq!(
+            candidates[2],
+            Candidate {
+                foundation: "842163049".to_owned(),
+                component: 1,
+                protocol: "udp".to_owned(),
+                priority: 1686052607,
+                address: "203.0.113.7".to_owned(),
+                port: 54321,
+                candidate_type: CandidateType::ServerReflexive,
+                related_address: Some("192.168.1.20".to_owned()),
+                related_port: Some(54321),
+                tcp_type: None,
+                extensions: vec![
+                    ("generation".to_owned(), Some("0".to_owned())),
+                    ("network-id".to_owned(), Some("1".to_owned())),
+                    ("network-cost".to_owned(), Some("10".to_owned())),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn firefox_offer() {
+        let sdp = Sdp::parse(FIREFOX).unwrap();
+        assert_eq!(sdp.attribute("msid-semantic"), Some("WMS *"));
+        assert_eq!(sdp.ice_ufrag(), Some("3c9a1f2e"));
+        assert_eq!(sdp.media[2].max_message_size(), Some(1073741823));
+        assert_eq!(sdp.media[0].attribute("end-of-candidates"), Some(""));
+        assert_eq!(
+            sdp.media[0].attribute("extmap"),
+            Some("1 urn:ietf:params:rtp-hdrext:ssrc-audio-level")
+        );
+        let video = &sdp.media[1];
+        let values = |name: &str| {
+            video
+                .attributes
+                .iter()
+                .filter(|attribute| attribute.name == name)
+                .filter_map(|attribute| attribute.value.as_deref())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values("rid"), ["h send", "m send", "l send"]);
+        assert_eq!(video.attribute("simulcast"), Some("send h;m;l"));
+        assert_eq!(values("ssrc-group").len(), 3);
+        assert!(values("extmap")
+            .contains(&"6/recvonly http://www.webrtc.org/experiments/rtp-hdrext/playout-delay"));
+        let candidates = sdp.candidates();
+        assert_eq!(candidates.len(), 3);
+        assert!(!candidates[0].has_ip_address());
+        assert_eq!(
+            candidates[0].address,
+            "4a5e8c3b-1f2d-4e6a-9b7c-0d1e2f3a4b5c.local"
+        );
+        assert!(candidates[2].has_ip_address());
+        assert_eq!(candidates[2].related_port, Some(0));
+    }
+
+    #[test]
+    fn webrtc_rs_offer() {
+        let sdp = Sdp::parse(WEBRTC_RS).unwrap();
+        assert_eq!(sdp.ice_ufrag(), Some("UvnRkmTmYXgrdEDk"));
+        assert_eq!(sdp.media[0].max_message_size(), None);
+        let candidates = sdp.candidates();
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[1].component, 2);
+        assert_eq!(candidates[2].candidate_type, CandidateType::ServerReflexive);
+    }
+
+    #[test]
+    fn line_endings_are_normalized() {
+        let sdp = Sdp::parse(&CHROME.replace("\r\n", "\n\n")).unwrap();
+        assert_eq!(sdp.to_string(), CHROME);
+    }
+
+    #[test]
+    fn munging() {
+        let mut sdp = Sdp::parse(FIREFOX).unwrap();
+        sdp.retain_candidates(|candidate| !candidate.has_ip_address());
+        sdp.media[2].set_max_message_size(65536);
+        sdp.remove_attribute("ice-options");
+        let munged = sdp.to_string();
+        assert!(!munged.contains("203.0.113.7"));
+        assert!(!munged.contains("ice-options"));
+        assert!(munged.contains("a=max-message-size:65536\r\n"));
+        assert_eq!(Sdp::parse(&munged).unwrap().candidates().len(), 2);
+    }
+
+    #[test]
+    fn unpaired_candidate_tokens_are_kept() {
+        let line = "candidate:1 1 udp 2122260223 192.168.1.20 54321 typ host generation 0 ufrag";
+        let candidate = line.parse::<Candidate>().unwrap();
+        assert_eq!(
+            candidate.extensions,
+            [
+                ("generation".to_owned(), Some("0".to_owned())),
+                ("ufrag".to_owned(), None),
+            ]
+        );
+        assert_eq!(candidate.to_string(), line);
+        assert_eq!(
+            format!("a={}", line).parse::<Candidate>().unwrap(),
+            candidate
+        );
+    }
+
+    #[test]
+    fn invalid_candidates_are_rejected() {
+        for line in [
+            "candidate:1 1 udp 2122260223 192.168.1.20 54321 host",
+            "candidate:1 1 udp 2122260223 192.168.1.20 54321 typ lan",
+            "candidate:1 1 udp priority 192.168.1.20 54321 typ host",
+            "candidate:1 1 udp 2122260223 192.168.1.20 54321 typ srflx rport none",
+        ] {
+            assert_eq!(line.parse::<Candidate>(), Err(Error::FailedToParseSdp));
+        }
+    }
+
+    #[test]
+    fn missing_version_is_rejected() {
+        assert_eq!(Sdp::parse("s=-\r\nt=0 0\r\n"), Err(Error::FailedToParseSdp));
+    }
+}
@@ -0,0 +1,311 @@
+use std::{
+    collections::HashMap,
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    task::Poll,
+};
+
+use futures::{
+    channel::mpsc,
+    future::{self, Either},
+    lock::Mutex as AsyncMutex,
+    stream::{self, Repeat, SelectAll, Zip},
+    StreamExt,
+};
+
+use crate::{
+    AcceptedDataChannel, AcceptedPeer, DataChannel, Error, PeerConnection, PeerConnectionState,
+    RtcListener,
+};
+
+/// Identifies a client of a [`Server`]. Ids are never reused by the same server.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientId(pub u64);
+
+impl std::fmt::Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    Connected(ClientId),
+    /// The client closed its connection or failed. Not emitted for clients removed with
+    /// [`Server::disconnect`].
+    Disconnected(ClientId),
+    Message {
+        client_id: ClientId,
+        data: Vec<u8>,
+    },
+}
+
+struct Client {
+    peer: PeerConnection,
+    data_channel: DataChannel,
+}
+
+/// The messages of one client, tagged with its id.
+type ClientMessages = Zip<Repeat<ClientId>, mpsc::UnboundedReceiver<Vec<u8>>>;
+
+enum Notice {
+    Disconnected(ClientId),
+    /// Wakes [`Server::next_event`] after [`Server::close`].
+    Closed,
+}
+
+/// What [`Server::next_event`] waits on, which only one caller at a time can poll.
+struct Inbox {
+    listener: Option<RtcListener>,
+    notices: mpsc::UnboundedReceiver<Notice>,
+    messages: SelectAll<ClientMessages>,
+}
+
+/// Tracks the clients accepted by an [`RtcListener`] and routes messages by [`ClientId`].
+///
+/// Messages are exchanged over the first data channel each client opens while connecting;
+/// clients that connect without one are closed. A client is connected once that channel is
+/// open, and no message it sends is lost. Clients are accepted and events are emitted while
+/// [`Server::next_event`] is awaited. All methods take `&self`, so the server can be shared
+/// between a task awaiting events and one sending messages.
+pub struct Server {
+    inbox: AsyncMutex<Inbox>,
+    notice_sender: mpsc::UnboundedSender<Notice>,
+    clients: Mutex<HashMap<ClientId, Client>>,
+    next_client_id: AtomicU64,
+    closed: AtomicBool,
+}
+
+impl Server {
+    pub fn new(listener: RtcListener) -> Self {
+        let (notice_sender, notices) = mpsc::unbounded();
+        Server {
+            inbox: AsyncMutex::new(Inbox {
+                listener: Some(listener),
+                notices,
+                messages: SelectAll::new(),
+            }),
+            notice_sender,
+            clients: Mutex::new(HashMap::new()),
+            next_client_id: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Waits for the next event. Returns [`Error::ListenerClosed`] once the listener has closed
+    /// and every client has disconnected.
+    pub async fn next_event(&self) -> Result<ServerEvent, Error> {
+        let mut inbox = self.inbox.lock().await;
+        let Inbox {
+            listener,
+            notices,
+            messages,
+        } = &mut *inbox;
+        loop {
+            if self.closed.load(Ordering::SeqCst) {
+                *listener = None;
+            }
+            if listener.is_none() && self.clients.lock().unwrap().is_empty() {
+                return Err(Error::ListenerClosed);
+            }
+            let received = future::poll_fn(|cx| {
+                if let Poll::Ready(notice) = notices.poll_next_unpin(cx) {
+                    return Poll::Ready(Either::Left(notice));
+                }
+                // an empty set of streams is ready with `None`, which only means no client
+                // is connected
+                if let Poll::Ready(Some(message)) = messages.poll_next_unpin(cx) {
+                    return Poll::Ready(Either::Right(message));
+                }
+                Poll::Pending
+            });
+            let received = match listener.as_mut() {
+                Some(accepting) => {
+                    // only the output is kept, so the listener can be cleared below
+                    let next = match future::select(pin!(accepting.accept()), pin!(received)).await
+                    {
+                        Either::Left((accepted, _)) => Either::Left(accepted),
+                        Either::Right((received, _)) => Either::Right(received),
+                    };
+                    match next {
+                        Either::Left(Ok(accepted)) => {
+                            match self.register(accepted, messages).await {
+                                Some(client_id) => return Ok(ServerEvent::Connected(client_id)),
+                                None => continue,
+                            }
+                        }
+                        Either::Left(Err(Error::ListenerClosed)) => {
+                            *listener = None;
+                            continue;
+                        }
+                        Either::Left(Err(err)) => return Err(err),
+                        Either::Right(received) => received,
+                    }
+                }
+                None => received.await,
+            };
+            match received {
+                Either::Left(Some(Notice::Disconnected(client_id))) => {
+                    // the channel and the peer can both report the same disconnect
+                    let client = self.clients.lock().unwrap().remove(&client_id);
+                    if let Some(client) = client {
+                        _ = client.peer.close().await;
+                        return Ok(ServerEvent::Disconnected(client_id));
+                    }
+                }
+                Either::Left(Some(Notice::Closed)) => {}
+                // the server holds a sender, so notices never end
+                Either::Left(None) => return Err(Error::ListenerClosed),
+                Either::Right((client_id, data)) => {
+                    // messages still queued from clients that were removed are dropped
+                    if self.contains(client_id) {
+                        return Ok(ServerEvent::Message { client_id, data });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends `data` to one client.
+    pub async fn send(&self, client_id: ClientId, data: &[u8]) -> Result<(), Error> {
+        let data_channel = self
+            .clients
+            .lock()
+            .unwrap()
+            .get(&client_id)
+            .map(|client| client.data_channel.clone())
+            .ok_or(Error::UnknownClient)?;
+        data_channel.send(data).await
+    }
+
+    /// Sends `data` to every client. Clients that cannot be sent to are skipped, and soon
+    /// disconnect.
+    pub async fn broadcast(&self, data: &[u8]) {
+        self.broadcast_except(&[], data).await;
+    }
+
+    /// Sends `data` to every client, except those in `excluded`.
+    pub async fn broadcast_except(&self, excluded: &[ClientId], data: &[u8]) {
+        let data_channels = self.data_channels(|client_id| !excluded.contains(client_id));
+        send_all(data_channels, data).await;
+    }
+
+    /// Sends `data` to the clients in `client_ids`. Unknown clients are skipped.
+    pub async fn multicast(&self, client_ids: &[ClientId], data: &[u8]) {
+        let data_channels = self.data_channels(|client_id| client_ids.contains(client_id));
+        send_all(data_channels, data).await;
+    }
+
+    /// Ids of the connected clients.
+    pub fn clients(&self) -> Vec<ClientId> {
+        self.clients.lock().unwrap().keys().copied().collect()
+    }
+
+    pub fn contains(&self, client_id: ClientId) -> bool {
+        self.clients.lock().unwrap().contains_key(&client_id)
+    }
+
+    /// Closes the connection to a client and forgets it, returning `false` if it was unknown.
+    pub async fn disconnect(&self, client_id: ClientId) -> bool {
+        let client = self.clients.lock().unwrap().remove(&client_id);
+        match client {
+            Some(client) => {
+                _ = client.peer.close().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops accepting clients and disconnects every client. Does not wait for a pending
+    /// [`Server::next_event`], which then returns [`Error::ListenerClosed`].
+    pub async fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        _ = self.notice_sender.unbounded_send(Notice::Closed);
+        let clients = std::mem::take(&mut *self.clients.lock().unwrap());
+        for client in clients.into_values() {
+            _ = client.peer.close().await;
+        }
+    }
+
+    async fn register(
+        &self,
+        accepted: AcceptedPeer,
+        messages: &mut SelectAll<ClientMessages>,
+    ) -> Option<ClientId> {
+        let AcceptedPeer {
+            peer,
+            data_channels,
+            ..
+        } = accepted;
+        let Some(AcceptedDataChannel {
+            data_channel,
+            messages: data_channel_messages,
+        }) = data_channels.into_iter().next()
+        else {
+            _ = peer.close().await;
+            return None;
+        };
+        let client_id = ClientId(self.next_client_id.fetch_add(1, Ordering::Relaxed));
+
+        let notice_sender = self.notice_sender.clone();
+        data_channel.on_close(Box::new(move || {
+            _ = notice_sender.unbounded_send(Notice::Disconnected(client_id));
+            Box::pin(async {})
+        }));
+        let notice_sender = self.notice_sender.clone();
+        peer.on_connection_state_change(Box::new(move |state| {
+            if matches!(
+                state,
+                PeerConnectionState::Failed | PeerConnectionState::Closed
+            ) {
+                _ = notice_sender.unbounded_send(Notice::Disconnected(client_id));
+            }
+            Box::pin(async {})
+        }));
+
+        let mut clients = self.clients.lock().unwrap();
+        // checked under the lock, so a concurrent close either takes this client or is seen
+        // here
+        if self.closed.load(Ordering::SeqCst) {
+            drop(clients);
+            _ = peer.close().await;
+            return None;
+        }
+        clients.insert(client_id, Client { peer, data_channel });
+        drop(clients);
+        messages.push(stream::repeat(client_id).zip(data_channel_messages));
+        Some(client_id)
+    }
+
+    fn data_channels(&self, include: impl Fn(&ClientId) -> bool) -> Vec<DataChannel> {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(client_id, _)| include(client_id))
+            .map(|(_, client)| client.data_channel.clone())
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server")
+            .field("clients", &self.clients())
+            .finish_non_exhaustive()
+    }
+}
+
+async fn send_all(data_channels: Vec<DataChannel>, data: &[u8]) {
+    future::join_all(
+        data_channels
+            .iter()
+            .map(|data_channel| data_channel.send(data)),
+    )
+    .await;
+}
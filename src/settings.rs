@@ -0,0 +1,245 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    ops::RangeInclusive,
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::Error;
+
+pub type InterfaceFilterFn = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+pub type IpFilterFn = Arc<dyn Fn(IpAddr) -> bool + Send + Sync>;
+
+/// Settings for native peers that have no equivalent in the browser, where they are ignored.
+#[derive(Default, Clone)]
+pub struct NativeSettings {
+    /// UDP ports used for host candidates. Any port is used if `None`.
+    pub ephemeral_udp_port_range: Option<RangeInclusive<u16>>,
+    /// A single UDP socket shared by every peer created with these settings, instead of a
+    /// socket per peer. Cannot be combined with `ephemeral_udp_port_range`. Unless
+    /// `network_types` is set, candidates are only gathered for the socket's address family.
+    pub udp_mux: Option<UdpMux>,
+    /// External IPs to advertise in place of local addresses when behind a 1:1 NAT, such as
+    /// a port-forwarded server.
+    pub nat_1to1_ips: Vec<IpAddr>,
+    pub nat_1to1_ip_candidate_type: Nat1To1CandidateType,
+    /// Network types used to gather candidates. All UDP types are used if empty.
+    ///
+    /// webrtc-rs does not gather ICE-TCP candidates, so native peers only ever use UDP, and
+    /// TCP is only reachable through a TURN server.
+    pub network_types: Vec<NetworkType>,
+    /// Only interfaces whose name is accepted by this filter are used to gather candidates.
+    pub interface_filter: Option<InterfaceFilterFn>,
+    /// Only local IPs accepted by this filter are used to gather candidates.
+    pub ip_filter: Option<IpFilterFn>,
+    pub mdns_mode: MdnsMode,
+    pub ice_timeouts: IceTimeouts,
+    /// Skips registering media codecs and RTP interceptors, which saves memory and setup time
+    /// for peers that only use data channels. Media tracks cannot be negotiated.
+    pub data_channels_only: bool,
+    /// Runs ICE-lite, for peers with a publicly reachable address. Only host candidates are
+    /// gathered, and the peer answers connectivity checks instead of sending its own, so the
+    /// remote peer must run full ICE.
+    pub ice_lite: bool,
+}
+
+impl std::fmt::Debug for NativeSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeSettings")
+            .field("ephemeral_udp_port_range", &self.ephemeral_udp_port_range)
+            .field("udp_mux", &self.udp_mux)
+            .field("nat_1to1_ips", &self.nat_1to1_ips)
+            .field(
+                "nat_1to1_ip_candidate_type",
+                &self.nat_1to1_ip_candidate_type,
+            )
+            .field("network_types", &self.network_types)
+            .field("interface_filter", &self.interface_filter.is_some())
+            .field("ip_filter", &self.ip_filter.is_some())
+            .field("mdns_mode", &self.mdns_mode)
+            .field("ice_timeouts", &self.ice_timeouts)
+            .field("data_channels_only", &self.data_channels_only)
+            .field("ice_lite", &self.ice_lite)
+            .finish()
+    }
+}
+
+/// A UDP socket that many peers can share, so that a server only needs one open port.
+#[derive(Clone)]
+pub struct UdpMux(
+    #[cfg(not(target_arch = "wasm32"))] Arc<webrtc::ice::udp_mux::UDPMuxDefault>,
+    #[cfg(not(target_arch = "wasm32"))] SocketAddr,
+);
+
+impl UdpMux {
+    /// Binds the shared socket, e.g. to `0.0.0.0:3478`. Unsupported in the browser.
+    pub async fn bind(address: SocketAddr) -> Result<Self, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use webrtc::ice::udp_mux::{UDPMuxDefault, UDPMuxParams};
+            let socket = tokio::net::UdpSocket::bind(address)
+                .await
+                .map_err(|_| Error::FailedToBindSocket)?;
+            let local_address = socket.local_addr().map_err(|_| Error::FailedToBindSocket)?;
+            Ok(UdpMux(
+                UDPMuxDefault::new(UDPMuxParams::new(socket)),
+                local_address,
+            ))
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            _ = address;
+            Err(Error::Unsupported("UDP sockets in the browser"))
+        }
+    }
+
+    /// The address the socket is bound to, which tells the port when binding to port 0.
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Some(self.1)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            None
+        }
+    }
+
+    /// Closes the socket. Peers still using it are disconnected.
+    pub async fn close(&self) -> Result<(), Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            use webrtc::ice::udp_mux::UDPMux;
+            self.0.close().await.map_err(|_| Error::FailedToClose)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Debug for UdpMux {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UdpMux")
+            .field("local_address", &self.local_address())
+            .finish()
+    }
+}
+
+/// How the addresses in [`NativeSettings::nat_1to1_ips`] are advertised.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Nat1To1CandidateType {
+    /// Replace the address of host candidates.
+    #[default]
+    Host,
+    /// Add server reflexive candidates alongside the host candidates.
+    ServerReflexive,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NetworkType {
+    Udp4,
+    Udp6,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MdnsMode {
+    Disabled,
+    /// Resolve remote mDNS candidates, but do not hide local addresses behind mDNS names.
+    #[default]
+    QueryOnly,
+    /// Resolve remote mDNS candidates and advertise host candidates under mDNS names.
+    QueryAndGather,
+}
+
+/// ICE timeouts. Defaults of the underlying implementation are used for `None` values.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IceTimeouts {
+    /// How long without network activity before the connection is considered disconnected.
+    pub disconnected: Option<Duration>,
+    /// How long after disconnecting before the connection is considered failed.
+    pub failed: Option<Duration>,
+    /// How often keepalive packets are sent when there is no other traffic.
+    pub keepalive_interval: Option<Duration>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl NativeSettings {
+    pub(crate) fn setting_engine(
+        &self,
+    ) -> Result<webrtc::api::setting_engine::SettingEngine, Error> {
+        use webrtc::{
+            api::setting_engine::SettingEngine,
+            ice::{
+                mdns::MulticastDnsMode,
+                network_type::NetworkType as NativeNetworkType,
+                udp_network::{EphemeralUDP, UDPNetwork},
+            },
+            ice_transport::ice_candidate_type::RTCIceCandidateType,
+        };
+        let mut setting_engine = SettingEngine::default();
+        if self.udp_mux.is_some() && self.ephemeral_udp_port_range.is_some() {
+            return Err(Error::InvalidNativeSettings);
+        }
+        if let Some(udp_mux) = &self.udp_mux {
+            setting_engine.set_udp_network(UDPNetwork::Muxed(udp_mux.0.clone()));
+            // candidates of the other family would be advertised on the shared port, but
+            // never connect
+            if self.network_types.is_empty() {
+                setting_engine.set_network_types(vec![if udp_mux.1.is_ipv4() {
+                    NativeNetworkType::Udp4
+                } else {
+                    NativeNetworkType::Udp6
+                }]);
+            }
+        }
+        if let Some(ephemeral_udp_port_range) = &self.ephemeral_udp_port_range {
+            let ephemeral_udp = EphemeralUDP::new(
+                *ephemeral_udp_port_range.start(),
+                *ephemeral_udp_port_range.end(),
+            )
+            .map_err(|_| Error::InvalidNativeSettings)?;
+            setting_engine.set_udp_network(UDPNetwork::Ephemeral(ephemeral_udp));
+        }
+        if !self.nat_1to1_ips.is_empty() {
+            setting_engine.set_nat_1to1_ips(
+                self.nat_1to1_ips.iter().map(|ip| ip.to_string()).collect(),
+                match self.nat_1to1_ip_candidate_type {
+                    Nat1To1CandidateType::Host => RTCIceCandidateType::Host,
+                    Nat1To1CandidateType::ServerReflexive => RTCIceCandidateType::Srflx,
+                },
+            );
+        }
+        if !self.network_types.is_empty() {
+            setting_engine.set_network_types(
+                self.network_types
+                    .iter()
+                    .map(|network_type| match network_type {
+                        NetworkType::Udp4 => NativeNetworkType::Udp4,
+                        NetworkType::Udp6 => NativeNetworkType::Udp6,
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(interface_filter) = self.interface_filter.clone() {
+            setting_engine
+                .set_interface_filter(Box::new(move |interface: &str| interface_filter(interface)));
+        }
+        if let Some(ip_filter) = self.ip_filter.clone() {
+            setting_engine.set_ip_filter(Box::new(move |ip: IpAddr| ip_filter(ip)));
+        }
+        setting_engine.set_ice_multicast_dns_mode(match self.mdns_mode {
+            MdnsMode::Disabled => MulticastDnsMode::Disabled,
+            MdnsMode::QueryOnly => MulticastDnsMode::QueryOnly,
+            MdnsMode::QueryAndGather => MulticastDnsMode::QueryAndGather,
+        });
+        setting_engine.set_lite(self.ice_lite);
+        setting_engine.set_ice_timeouts(
+            self.ice_timeouts.disconnected,
+            self.ice_timeouts.failed,
+            self.ice_timeouts.keepalive_interval,
+        );
+        Ok(setting_engine)
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Error, IceCandidateInit, SdpType, SessionDescription};
+
+/// A message exchanged over a signaling channel.
+///
+/// With the `serde` feature, descriptions and candidates serialize to the same JSON as the
+/// browser's `RTCSessionDescriptionInit` and `RTCIceCandidateInit`, for example
+/// `{"type":"offer","sdp":"..."}` or
+/// `{"type":"candidate","candidate":"...","sdpMid":"0","sdpMLineIndex":0}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename_all = "kebab-case")
+)]
+pub enum SignalMessage {
+    Offer { sdp: String },
+    Answer { sdp: String },
+    Candidate(IceCandidateInit),
+    EndOfCandidates,
+    Bye,
+}
+
+impl From<IceCandidateInit> for SignalMessage {
+    fn from(value: IceCandidateInit) -> Self {
+        SignalMessage::Candidate(value)
+    }
+}
+
+impl From<Option<IceCandidateInit>> for SignalMessage {
+    fn from(value: Option<IceCandidateInit>) -> Self {
+        match value {
+            Some(ice_candidate) => SignalMessage::Candidate(ice_candidate),
+            None => SignalMessage::EndOfCandidates,
+        }
+    }
+}
+
+impl TryFrom<&SessionDescription> for SignalMessage {
+    type Error = Error;
+
+    fn try_from(value: &SessionDescription) -> Result<Self, Self::Error> {
+        match value.sdp_type() {
+            SdpType::Offer => Ok(SignalMessage::Offer { sdp: value.sdp() }),
+            SdpType::Answer => Ok(SignalMessage::Answer { sdp: value.sdp() }),
+            _ => Err(Error::UnexpectedSdpType),
+        }
+    }
+}
+
+impl TryFrom<&SignalMessage> for SessionDescription {
+    type Error = Error;
+
+    fn try_from(value: &SignalMessage) -> Result<Self, Self::Error> {
+        match value {
+            SignalMessage::Offer { sdp } => SessionDescription::offer(sdp),
+            SignalMessage::Answer { sdp } => SessionDescription::answer(sdp),
+            _ => Err(Error::UnexpectedSignalMessage),
+        }
+    }
+}
+
+impl TryFrom<&SignalMessage> for Option<IceCandidateInit> {
+    type Error = Error;
+
+    fn try_from(value: &SignalMessage) -> Result<Self, Self::Error> {
+        match value {
+            SignalMessage::Candidate(ice_candidate) => Ok(Some(ice_candidate.clone())),
+            SignalMessage::EndOfCandidates => Ok(None),
+            _ => Err(Error::UnexpectedSignalMessage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SDP: &str = "v=0\r\n\
+                       o=- 0 0 IN IP4 127.0.0.1\r\n\
+                       s=-\r\n\
+                       t=0 0\r\n";
+
+    fn ice_candidate() -> IceCandidateInit {
+        IceCandidateInit {
+            candidate: "candidate:1 1 UDP 2122252543 192.168.1.20 50000 typ host".to_owned(),
+            sdp_mid: Some("0".to_owned()),
+            sdp_mline_index: Some(0),
+        }
+    }
+
+    #[test]
+    fn descriptions_convert() {
+        let offer = SessionDescription::offer(SDP).unwrap();
+        let message = SignalMessage::try_from(&offer).unwrap();
+        assert_eq!(
+            message,
+            SignalMessage::Offer {
+                sdp: SDP.to_owned()
+            }
+        );
+        let description = SessionDescription::try_from(&message).unwrap();
+        assert_eq!(description.sdp_type(), SdpType::Offer);
+        assert_eq!(description.sdp(), SDP);
+
+        let answer = SessionDescription::answer(SDP).unwrap();
+        assert_eq!(
+            SignalMessage::try_from(&answer).unwrap(),
+            SignalMessage::Answer {
+                sdp: SDP.to_owned()
+            }
+        );
+        let pranswer = SessionDescription::new(SdpType::Pranswer, SDP).unwrap();
+        assert_eq!(
+            SignalMessage::try_from(&pranswer),
+            Err(Error::UnexpectedSdpType)
+        );
+        assert_eq!(
+            SessionDescription::try_from(&SignalMessage::Bye).err(),
+            Some(Error::UnexpectedSignalMessage)
+        );
+    }
+
+    #[test]
+    fn candidates_convert() {
+        let message = SignalMessage::from(Some(ice_candidate()));
+        assert_eq!(message, SignalMessage::Candidate(ice_candidate()));
+        assert_eq!(
+            Option::<IceCandidateInit>::try_from(&message),
+            Ok(Some(ice_candidate()))
+        );
+        let message = SignalMessage::from(None);
+        assert_eq!(message, SignalMessage::EndOfCandidates);
+        assert_eq!(Option::<IceCandidateInit>::try_from(&message), Ok(None));
+        assert_eq!(
+            Option::<IceCandidateInit>::try_from(&SignalMessage::Bye),
+            Err(Error::UnexpectedSignalMessage)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_matches_the_browser() {
+        let cases = [
+            (
+                SignalMessage::Offer {
+                    sdp: SDP.to_owned(),
+                },
+                serde_json::json!({ "type": "offer", "sdp": SDP }),
+            ),
+            (
+                SignalMessage::Answer {
+                    sdp: SDP.to_owned(),
+                },
+                serde_json::json!({ "type": "answer", "sdp": SDP }),
+            ),
+            (
+                SignalMessage::Candidate(ice_candidate()),
+                serde_json::json!({
+                    "type": "candidate",
+                    "candidate": "candidate:1 1 UDP 2122252543 192.168.1.20 50000 typ host",
+                    "sdpMid": "0",
+                    "sdpMLineIndex": 0,
+                }),
+            ),
+            (
+                SignalMessage::EndOfCandidates,
+                serde_json::json!({ "type": "end-of-candidates" }),
+            ),
+            (SignalMessage::Bye, serde_json::json!({ "type": "bye" })),
+        ];
+        for (message, json) in cases {
+            assert_eq!(serde_json::to_value(&message).unwrap(), json);
+            assert_eq!(
+                serde_json::from_value::<SignalMessage>(json).unwrap(),
+                message
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn browser_json_deserializes() {
+        // as produced by JSON.stringify in the browser
+        let description: SessionDescription = serde_json::from_str(
+            r#"{"type":"answer","sdp":"v=0\r\no=- 0 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n"}"#,
+        )
+        .unwrap();
+        assert_eq!(description.sdp_type(), SdpType::Answer);
+        assert_eq!(description.sdp(), SDP);
+        assert_eq!(
+            serde_json::to_value(&description).unwrap(),
+            serde_json::json!({ "type": "answer", "sdp": SDP })
+        );
+
+        let ice_candidate: IceCandidateInit = serde_json::from_str(
+            r#"{"candidate":"candidate:1 1 UDP 2122252543 192.168.1.20 50000 typ host","sdpMid":"0","sdpMLineIndex":0,"usernameFragment":"EsAw"}"#,
+        )
+        .unwrap();
+        assert_eq!(ice_candidate, self::ice_candidate());
+        let ice_candidate: IceCandidateInit =
+            serde_json::from_str(r#"{"candidate":"","sdpMid":null,"sdpMLineIndex":null}"#).unwrap();
+        assert_eq!(ice_candidate, IceCandidateInit::default());
+    }
+}
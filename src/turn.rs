@@ -0,0 +1,223 @@
+//! Time-limited TURN credentials using the shared-secret scheme of the TURN REST API, as
+//! implemented by coturn's `use-auth-secret`.
+//!
+//! The username is `<expiry>:<user id>`, where `<expiry>` is a Unix timestamp in seconds,
+//! and the credential is `base64(hmac-sha1(secret, username))`.
+
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::{runtime::unix_time, Error, IceCredentialType, IceServer};
+
+#[derive(Clone)]
+pub struct TurnRestAuth {
+    secret: String,
+    ttl: Duration,
+    separator: char,
+}
+
+impl TurnRestAuth {
+    /// `secret` is coturn's `static-auth-secret`, and credentials are valid for `ttl`.
+    pub fn new(secret: &str, ttl: Duration) -> Self {
+        TurnRestAuth {
+            secret: secret.to_owned(),
+            ttl,
+            separator: ':',
+        }
+    }
+
+    /// Matches coturn's `rest-api-separator`, which defaults to `:`.
+    pub fn with_separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// An ICE server for `urls` with credentials valid from now until the TTL elapses.
+    ///
+    /// `user_id` is only informative to the TURN server, and may be omitted.
+    pub fn ice_server(&self, urls: &[&str], user_id: Option<&str>) -> IceServer {
+        let expires_at = unix_time().saturating_add(self.ttl).as_secs();
+        let (username, credential) = self.credentials_expiring_at(expires_at, user_id);
+        IceServer {
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            username: Some(username),
+            credential: Some(credential),
+            credential_type: IceCredentialType::Password,
+        }
+    }
+
+    /// Returns the username and credential expiring at `expires_at` seconds since the Unix
+    /// epoch.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use unirtc::turn::TurnRestAuth;
+    /// let auth = TurnRestAuth::new("north", Duration::from_secs(3600));
+    /// let (username, credential) = auth.credentials_expiring_at(1700000000, Some("alice"));
+    /// assert_eq!(username, "1700000000:alice");
+    /// assert_eq!(credential, "Cd/49soE35ICqcJF/bCTn8Z4OyE=");
+    ///
+    /// let (username, credential) = auth.credentials_expiring_at(1700000000, None);
+    /// assert_eq!(username, "1700000000");
+    /// assert_eq!(credential, "CWyHi3zCeWqXBir9thl4m+iZPRY=");
+    /// ```
+    pub fn credentials_expiring_at(
+        &self,
+        expires_at: u64,
+        user_id: Option<&str>,
+    ) -> (String, String) {
+        let username = match user_id {
+            Some(user_id) => format!("{}{}{}", expires_at, self.separator, user_id),
+            None => expires_at.to_string(),
+        };
+        let credential = STANDARD.encode(self.mac(&username).finalize().into_bytes());
+        (username, credential)
+    }
+
+    /// Checks a username and credential presented to the TURN server.
+    pub fn verify(&self, username: &str, credential: &str) -> Result<(), Error> {
+        self.verify_at(username, credential, unix_time().as_secs())
+    }
+
+    /// Like [`TurnRestAuth::verify`], at `now` seconds since the Unix epoch.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use unirtc::{turn::TurnRestAuth, Error};
+    /// let auth = TurnRestAuth::new("north", Duration::from_secs(3600));
+    /// let credential = "Cd/49soE35ICqcJF/bCTn8Z4OyE=";
+    /// assert_eq!(auth.verify_at("1700000000:alice", credential, 1699999000), Ok(()));
+    /// assert_eq!(
+    ///     auth.verify_at("1700000000:alice", credential, 1700000001),
+    ///     Err(Error::TurnCredentialExpired),
+    /// );
+    /// assert_eq!(
+    ///     auth.verify_at("1700000000:mallory", credential, 1699999000),
+    ///     Err(Error::InvalidTurnCredential),
+    /// );
+    /// ```
+    pub fn verify_at(&self, username: &str, credential: &str, now: u64) -> Result<(), Error> {
+        let expires_at = username
+            .split(self.separator)
+            .next()
+            .and_then(|expires_at| expires_at.parse::<u64>().ok())
+            .ok_or(Error::InvalidTurnCredential)?;
+        let credential = STANDARD
+            .decode(credential)
+            .map_err(|_| Error::InvalidTurnCredential)?;
+        // compared in constant time
+        self.mac(username)
+            .verify_slice(&credential)
+            .map_err(|_| Error::InvalidTurnCredential)?;
+        if now > expires_at {
+            return Err(Error::TurnCredentialExpired);
+        }
+        Ok(())
+    }
+
+    fn mac(&self, username: &str) -> Hmac<Sha1> {
+        let mut mac = Hmac::<Sha1>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(username.as_bytes());
+        mac
+    }
+}
+
+impl std::fmt::Debug for TurnRestAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TurnRestAuth")
+            .field("ttl", &self.ttl)
+            .field("separator", &self.separator)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `logen` is the static-auth-secret of coturn's REST API example scripts. The expected
+    // credentials were computed with `openssl dgst -sha1 -hmac logen -binary | base64`,
+    // following coturn's `base64(hmac-sha1(static-auth-secret, username))`.
+    const SECRET: &str = "logen";
+    const EXPIRES_AT: u64 = 1433186213;
+
+    #[test]
+    fn coturn_known_answers() {
+        let auth = TurnRestAuth::new(SECRET, Duration::from_secs(3600));
+        assert_eq!(
+            auth.credentials_expiring_at(EXPIRES_AT, Some("ninefingers")),
+            (
+                "1433186213:ninefingers".to_owned(),
+                "6mpHKyHdlcf4nxozhNVamKgmdCk=".to_owned()
+            )
+        );
+        assert_eq!(
+            auth.credentials_expiring_at(EXPIRES_AT, None),
+            (
+                "1433186213".to_owned(),
+                "d17Isw84vR1iwh14op8DaCnuk+U=".to_owned()
+            )
+        );
+        let auth = auth.with_separator(';');
+        assert_eq!(
+            auth.credentials_expiring_at(EXPIRES_AT, Some("ninefingers")),
+            (
+                "1433186213;ninefingers".to_owned(),
+                "YtU90CVEXSfaWVdZM2XN8Y4ev+o=".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn verification() {
+        let auth = TurnRestAuth::new(SECRET, Duration::from_secs(3600));
+        let credential = "6mpHKyHdlcf4nxozhNVamKgmdCk=";
+        assert_eq!(
+            auth.verify_at("1433186213:ninefingers", credential, EXPIRES_AT),
+            Ok(())
+        );
+        assert_eq!(
+            auth.verify_at("1433186213:ninefingers", credential, EXPIRES_AT + 1),
+            Err(Error::TurnCredentialExpired)
+        );
+        for (username, credential) in [
+            ("1433186214:ninefingers", credential),
+            ("1433186213:ninefingers", "d17Isw84vR1iwh14op8DaCnuk+U="),
+            ("1433186213:ninefingers", "not base64"),
+            ("ninefingers", credential),
+        ] {
+            assert_eq!(
+                auth.verify_at(username, credential, EXPIRES_AT),
+                Err(Error::InvalidTurnCredential),
+                "{}",
+                username
+            );
+        }
+        let other_secret = TurnRestAuth::new("north", Duration::from_secs(3600));
+        assert_eq!(
+            other_secret.verify_at("1433186213:ninefingers", credential, EXPIRES_AT),
+            Err(Error::InvalidTurnCredential)
+        );
+    }
+
+    #[test]
+    fn ice_servers_expire_after_the_ttl() {
+        let auth = TurnRestAuth::new(SECRET, Duration::from_secs(3600));
+        let before = unix_time().as_secs();
+        let ice_server = auth.ice_server(&["turn:turn.example.com"], Some("ninefingers"));
+        let after = unix_time().as_secs();
+        let username = ice_server.username.unwrap();
+        let (expires_at, user_id) = username.split_once(':').unwrap();
+        let expires_at = expires_at.parse::<u64>().unwrap();
+        assert!((before + 3600..=after + 3600).contains(&expires_at));
+        assert_eq!(user_id, "ninefingers");
+        assert_eq!(ice_server.urls, ["turn:turn.example.com"]);
+        assert_eq!(ice_server.credential_type, IceCredentialType::Password);
+        let credential = ice_server.credential.unwrap();
+        assert_eq!(auth.verify(&username, &credential), Ok(()));
+    }
+}
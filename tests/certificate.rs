@@ -0,0 +1,48 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use unirtc as rtc;
+
+#[tokio::test]
+async fn pem_round_trip_keeps_the_fingerprint() {
+    let certificate = rtc::Certificate::generate().await.unwrap();
+    let pem = certificate.to_pem().unwrap();
+    let loaded = rtc::Certificate::from_pem(&pem).unwrap();
+    assert!(!certificate.fingerprints().is_empty());
+    assert_eq!(loaded.fingerprints(), certificate.fingerprints());
+    assert!(matches!(
+        rtc::Certificate::from_pem("not a certificate"),
+        Err(rtc::Error::FailedToParseCertificate)
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn peers_announce_their_certificate() {
+    let certificate = rtc::Certificate::generate().await.unwrap();
+    let offerer = rtc::PeerConnection::new(&rtc::Configuration {
+        certificates: vec![certificate.clone()],
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let answerer = common::peer().await;
+    assert_eq!(offerer.local_fingerprint().await, None);
+    common::connect(&offerer, &answerer).await;
+
+    let offerer_fingerprint = offerer.local_fingerprint().await.unwrap();
+    assert_eq!(offerer_fingerprint, certificate.fingerprints()[0]);
+    // each peer sees the fingerprint the other announced
+    assert_eq!(
+        answerer.remote_fingerprint().await,
+        Some(offerer_fingerprint)
+    );
+    assert_eq!(
+        offerer.remote_fingerprint().await,
+        answerer.local_fingerprint().await
+    );
+    assert!(answerer.local_fingerprint().await.is_some());
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
@@ -0,0 +1,140 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc as rtc;
+
+async fn clock_channels(
+    offerer: &rtc::PeerConnection,
+    answerer: &rtc::PeerConnection,
+) -> (rtc::DataChannel, rtc::DataChannel) {
+    let mut remote_channels = common::data_channels(answerer);
+    let local_channel = offerer
+        .create_data_channel(rtc::ClockSync::LABEL, rtc::ClockSync::data_channel_init())
+        .await
+        .unwrap();
+    let open = common::opened(&local_channel);
+    common::negotiate(offerer, answerer).await;
+    common::timeout(open).await.unwrap();
+    let remote_channel = common::timeout(remote_channels.next()).await.unwrap();
+    common::timeout(common::opened(&remote_channel))
+        .await
+        .unwrap();
+    (local_channel, remote_channel)
+}
+
+/// The send time of the next clock sync request.
+async fn next_request(requests: &mut mpsc::UnboundedReceiver<Vec<u8>>) -> i64 {
+    let request = common::timeout(requests.next()).await.unwrap();
+    i64::from_le_bytes(request[1..9].try_into().unwrap())
+}
+
+/// A clock sync response, as the remote peer would send it.
+fn response(t0: i64, t1: i64, t2: i64) -> Vec<u8> {
+    let mut packet = vec![1];
+    for time in [t0, t1, t2] {
+        packet.extend_from_slice(&time.to_le_bytes());
+    }
+    packet
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn estimates_until_the_channel_closes() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let (local_channel, remote_channel) = clock_channels(&offerer, &answerer).await;
+    let local =
+        Arc::new(rtc::ClockSync::new(local_channel).with_interval(Duration::from_millis(50)));
+    let remote =
+        Arc::new(rtc::ClockSync::new(remote_channel).with_interval(Duration::from_millis(50)));
+    let local_run = tokio::spawn({
+        let local = local.clone();
+        async move { local.run().await }
+    });
+    let remote_run = tokio::spawn({
+        let remote = remote.clone();
+        async move { remote.run().await }
+    });
+
+    common::timeout(async {
+        while local.estimate().is_none_or(|estimate| estimate.samples < 3) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await;
+    // both peers share a clock here
+    let estimate = local.estimate().unwrap();
+    assert!(
+        estimate.offset_micros.unsigned_abs() <= estimate.uncertainty.as_micros() as u64 + 1000,
+        "{:?}",
+        estimate
+    );
+
+    // the remote peer closing its end is noticed
+    answerer.close().await.unwrap();
+    common::timeout(local_run).await.unwrap();
+    remote.stop();
+    common::timeout(remote_run).await.unwrap();
+    assert!(remote.estimate().is_some());
+    offerer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn forged_responses_are_ignored() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let (local_channel, remote_channel) = clock_channels(&offerer, &answerer).await;
+    let mut requests = common::messages(&remote_channel);
+    let local =
+        Arc::new(rtc::ClockSync::new(local_channel).with_interval(Duration::from_millis(50)));
+    let local_run = tokio::spawn({
+        let local = local.clone();
+        async move { local.run().await }
+    });
+
+    // the first request is answered honestly
+    let t0 = next_request(&mut requests).await;
+    let now = rtc::ClockSync::local_now().as_micros() as i64;
+    remote_channel.send(&response(t0, now, now)).await.unwrap();
+    common::timeout(async {
+        while local.estimate().is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    let estimate = local.estimate().unwrap();
+
+    // times that overflow
+    let t0 = next_request(&mut requests).await;
+    remote_channel
+        .send(&response(t0, i64::MAX, i64::MIN))
+        .await
+        .unwrap();
+    // a negative delay, claiming a minute was spent answering
+    let t0 = next_request(&mut requests).await;
+    let now = rtc::ClockSync::local_now().as_micros() as i64;
+    remote_channel
+        .send(&response(t0, now, now + 60_000_000))
+        .await
+        .unwrap();
+    // a request never sent, and a truncated packet
+    let now = rtc::ClockSync::local_now().as_micros() as i64;
+    remote_channel
+        .send(&response(now + 1, now, now))
+        .await
+        .unwrap();
+    remote_channel.send(&[1, 0, 0]).await.unwrap();
+    // a later request shows the forged responses were handled
+    next_request(&mut requests).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(local.estimate(), Some(estimate));
+    assert!(!local_run.is_finished());
+
+    local.stop();
+    common::timeout(local_run).await.unwrap();
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
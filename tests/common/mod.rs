@@ -0,0 +1,103 @@
+//! Helpers for tests that connect two peers in the same process.
+
+#![allow(dead_code)]
+
+use std::{future::Future, time::Duration};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt,
+};
+use unirtc as rtc;
+
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn peer() -> rtc::PeerConnection {
+    rtc::PeerConnection::new(&rtc::Configuration::default())
+        .await
+        .unwrap()
+}
+
+/// Fails the test if `future` takes longer than [`TIMEOUT`].
+pub async fn timeout<F: Future>(future: F) -> F::Output {
+    tokio::time::timeout(TIMEOUT, future)
+        .await
+        .expect("timed out")
+}
+
+/// Resolves once `data_channel` is open.
+pub fn opened(data_channel: &rtc::DataChannel) -> oneshot::Receiver<()> {
+    let (open_sender, open_receiver) = oneshot::channel();
+    let open_sender = std::sync::Mutex::new(Some(open_sender));
+    data_channel.on_open(Box::new(move || {
+        if let Some(open_sender) = open_sender.lock().unwrap().take() {
+            _ = open_sender.send(());
+        }
+        Box::pin(async {})
+    }));
+    open_receiver
+}
+
+pub fn messages(data_channel: &rtc::DataChannel) -> mpsc::UnboundedReceiver<Vec<u8>> {
+    let (message_sender, message_receiver) = mpsc::unbounded();
+    data_channel.on_message(Box::new(move |message, _| {
+        _ = message_sender.unbounded_send(message);
+        Box::pin(async {})
+    }));
+    message_receiver
+}
+
+pub fn data_channels(peer: &rtc::PeerConnection) -> mpsc::UnboundedReceiver<rtc::DataChannel> {
+    let (data_channel_sender, data_channel_receiver) = mpsc::unbounded();
+    peer.on_data_channel(Box::new(move |data_channel| {
+        _ = data_channel_sender.unbounded_send(data_channel);
+        Box::pin(async {})
+    }));
+    data_channel_receiver
+}
+
+pub fn states(peer: &rtc::PeerConnection) -> mpsc::UnboundedReceiver<rtc::PeerConnectionState> {
+    let (state_sender, state_receiver) = mpsc::unbounded();
+    peer.on_connection_state_change(Box::new(move |state| {
+        _ = state_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+    state_receiver
+}
+
+/// Waits until `states` reports `state`.
+pub async fn reach(
+    states: &mut mpsc::UnboundedReceiver<rtc::PeerConnectionState>,
+    state: rtc::PeerConnectionState,
+) {
+    timeout(async { while states.next().await != Some(state) {} }).await
+}
+
+/// Negotiates without trickle ICE, so no candidates can arrive early.
+pub async fn negotiate(offerer: &rtc::PeerConnection, answerer: &rtc::PeerConnection) {
+    let offer = offerer.create_offer_complete(Some(TIMEOUT)).await.unwrap();
+    answerer.set_remote_description(&offer).await.unwrap();
+    let answer = answerer
+        .create_answer_complete(Some(TIMEOUT))
+        .await
+        .unwrap();
+    offerer.set_remote_description(&answer).await.unwrap();
+}
+
+/// Opens a data channel from `offerer` to `answerer`, returning both ends once open.
+pub async fn connect(
+    offerer: &rtc::PeerConnection,
+    answerer: &rtc::PeerConnection,
+) -> (rtc::DataChannel, rtc::DataChannel) {
+    let mut remote_channels = data_channels(answerer);
+    let local_channel = offerer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let open = opened(&local_channel);
+    negotiate(offerer, answerer).await;
+    timeout(open).await.unwrap();
+    let remote_channel = timeout(remote_channels.next()).await.unwrap();
+    timeout(opened(&remote_channel)).await.unwrap();
+    (local_channel, remote_channel)
+}
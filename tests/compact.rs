@@ -0,0 +1,47 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::StreamExt;
+use unirtc as rtc;
+
+#[tokio::test]
+async fn compact_descriptions_connect() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let mut remote_channels = common::data_channels(&answerer);
+    let local_channel = offerer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let open = common::opened(&local_channel);
+
+    let offer = offerer
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    let encoded_offer = rtc::compact::encode(&offer).unwrap();
+    assert!(encoded_offer.len() < offer.sdp().len() / 2);
+    answerer
+        .set_remote_description(&rtc::compact::decode(&encoded_offer).unwrap())
+        .await
+        .unwrap();
+    let answer = answerer
+        .create_answer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    let encoded_answer = rtc::compact::encode(&answer).unwrap();
+    offerer
+        .set_remote_description(&rtc::compact::decode(&encoded_answer).unwrap())
+        .await
+        .unwrap();
+
+    common::timeout(open).await.unwrap();
+    let remote_channel = common::timeout(remote_channels.next()).await.unwrap();
+    let mut messages = common::messages(&remote_channel);
+    local_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
@@ -0,0 +1,26 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use unirtc as rtc;
+
+#[tokio::test]
+async fn unsupported_options_are_rejected() {
+    for configuration in [
+        rtc::Configuration {
+            rtcp_mux_policy: rtc::RtcpMuxPolicy::Negotiate,
+            ..Default::default()
+        },
+        rtc::Configuration {
+            ice_candidate_pool_size: 1,
+            ..Default::default()
+        },
+        rtc::Configuration {
+            peer_identity: Some("peer@example.com".to_owned()),
+            ..Default::default()
+        },
+    ] {
+        assert!(matches!(
+            rtc::PeerConnection::new(&configuration).await,
+            Err(rtc::Error::Unsupported(_))
+        ));
+    }
+}
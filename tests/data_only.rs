@@ -0,0 +1,63 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::StreamExt;
+use unirtc::{self as rtc, sdp::Sdp};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn data_channels_only() {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            data_channels_only: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let offerer = factory.create().await.unwrap();
+    let answerer = factory.create().await.unwrap();
+    let (local_channel, remote_channel) = common::connect(&offerer, &answerer).await;
+
+    for description in [
+        offerer.local_description().await.unwrap(),
+        answerer.local_description().await.unwrap(),
+    ] {
+        let sdp = Sdp::from_session_description(&description).unwrap();
+        let media = sdp
+            .media
+            .iter()
+            .map(|media| media.description.media.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(media, ["application"]);
+    }
+
+    let mut messages = common::messages(&remote_channel);
+    local_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
+
+/// A data-channel-only peer still connects to a peer with the full media stack.
+#[tokio::test(flavor = "multi_thread")]
+async fn data_channels_only_connects_to_full_peer() {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            data_channels_only: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let offerer = factory.create().await.unwrap();
+    let answerer = common::peer().await;
+    let (local_channel, remote_channel) = common::connect(&offerer, &answerer).await;
+    let mut messages = common::messages(&local_channel);
+    remote_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
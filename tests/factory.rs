@@ -0,0 +1,62 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::StreamExt;
+use unirtc as rtc;
+
+fn factory() -> rtc::PeerFactory {
+    rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn factory_peers_connect() {
+    let factory = factory();
+    let offerer = factory.create().await.unwrap();
+    let answerer = factory.create().await.unwrap();
+    let (local_channel, remote_channel) = common::connect(&offerer, &answerer).await;
+    let mut messages = common::messages(&remote_channel);
+    local_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
+
+/// The setup the `peer_factory` example benchmarks, repeated on one factory.
+#[tokio::test(flavor = "multi_thread")]
+async fn factory_is_reusable() {
+    let factory = factory();
+    for _ in 0..20 {
+        let peer = factory.create().await.unwrap();
+        let _data_channel = peer
+            .create_data_channel("data", rtc::DataChannelInit::default())
+            .await
+            .unwrap();
+        let offer = peer.create_offer().await.unwrap();
+        peer.set_local_description(&offer).await.unwrap();
+        assert!(peer.local_description().await.is_some());
+        peer.close().await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn invalid_configurations_are_rejected() {
+    let configuration = rtc::Configuration {
+        ice_servers: vec![rtc::IceServer {
+            urls: vec!["stun://stun.example.com".to_owned()],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    // the error says which URL is wrong
+    assert!(matches!(
+        rtc::PeerFactory::new(&configuration, &rtc::NativeSettings::default()).err(),
+        Some(rtc::Error::InvalidConfiguration(
+            rtc::ConfigurationError::InvalidUrl { ice_server: 0, url, .. }
+        )) if url == "stun://stun.example.com"
+    ));
+}
@@ -0,0 +1,75 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use unirtc as rtc;
+
+async fn heartbeats(
+    offerer: &rtc::PeerConnection,
+    answerer: &rtc::PeerConnection,
+) -> (Arc<rtc::Heartbeat>, Arc<rtc::Heartbeat>) {
+    let mut remote_channels = common::data_channels(answerer);
+    let local_channel = offerer
+        .create_data_channel(rtc::Heartbeat::LABEL, rtc::Heartbeat::data_channel_init())
+        .await
+        .unwrap();
+    let open = common::opened(&local_channel);
+    common::negotiate(offerer, answerer).await;
+    common::timeout(open).await.unwrap();
+    let remote_channel = common::timeout(remote_channels.next()).await.unwrap();
+    common::timeout(common::opened(&remote_channel))
+        .await
+        .unwrap();
+    let heartbeat = |data_channel| {
+        Arc::new(
+            rtc::Heartbeat::new(data_channel)
+                .with_interval(Duration::from_millis(50))
+                .with_timeout(Duration::from_millis(500)),
+        )
+    };
+    (heartbeat(local_channel), heartbeat(remote_channel))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn measures_round_trips_until_the_remote_goes_silent() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let (local, remote) = heartbeats(&offerer, &answerer).await;
+    assert_eq!(local.stats(), rtc::RttStats::default());
+    let local_run = tokio::spawn({
+        let local = local.clone();
+        async move { local.run().await }
+    });
+    let remote_run = tokio::spawn({
+        let remote = remote.clone();
+        async move { remote.run().await }
+    });
+
+    common::timeout(async {
+        while local.stats().samples < 3 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await;
+    let stats = local.stats();
+    let (latest, mean, min) = (
+        stats.latest.unwrap(),
+        stats.mean.unwrap(),
+        stats.min.unwrap(),
+    );
+    assert!(min <= latest && min <= mean, "{:?}", stats);
+    assert!(latest < common::TIMEOUT, "{:?}", stats);
+
+    // pings are only answered while running
+    remote_run.abort();
+    assert_eq!(
+        common::timeout(local_run).await.unwrap(),
+        Err(rtc::Error::HeartbeatTimedOut)
+    );
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
@@ -0,0 +1,126 @@
+#![cfg(all(feature = "http", not(target_arch = "wasm32")))]
+
+mod common;
+
+use std::sync::Arc;
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc::{self as rtc, http};
+
+/// Serves signaling on a local port, echoing messages on every data channel. Peers created by
+/// the server are sent to the returned receiver.
+fn serve(
+    bearer_token: Option<&str>,
+) -> (String, mpsc::UnboundedReceiver<Arc<rtc::PeerConnection>>) {
+    let (peer_sender, peers) = mpsc::unbounded();
+    let mut server = http::HttpSignalingServer::new(
+        rtc::Configuration::default(),
+        Box::new(move |peer| {
+            peer.on_data_channel(Box::new(|data_channel| {
+                let echo = data_channel.clone();
+                data_channel.on_message(Box::new(move |message, _| {
+                    let echo = echo.clone();
+                    Box::pin(async move {
+                        _ = echo.send(&message).await;
+                    })
+                }));
+                Box::pin(async {})
+            }));
+            _ = peer_sender.unbounded_send(peer);
+            Box::pin(async {})
+        }),
+    );
+    if let Some(bearer_token) = bearer_token {
+        server = server.with_bearer_token(bearer_token);
+    }
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/whip", listener.local_addr().unwrap());
+    tokio::spawn(async move { server.serve(listener).await });
+    (endpoint, peers)
+}
+
+/// Creates a peer with an open data channel, signaled through `client`.
+async fn connect(
+    client: &http::HttpSignalingClient,
+) -> Result<(rtc::PeerConnection, rtc::DataChannel, http::HttpSession), rtc::Error> {
+    let peer = common::peer().await;
+    let data_channel = peer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await?;
+    let open = common::opened(&data_channel);
+    let offer = peer.create_offer_complete(Some(common::TIMEOUT)).await?;
+    let (session, answer) = client.send_offer(&offer).await?;
+    peer.set_remote_description(&answer).await?;
+    common::timeout(open).await.unwrap();
+    Ok((peer, data_channel, session))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn offer_answer_over_http() {
+    let (endpoint, _peers) = serve(None);
+    let client = http::HttpSignalingClient::new(endpoint.clone());
+    let (peer, data_channel, session) = connect(&client).await.unwrap();
+    assert!(session.url().starts_with(&endpoint));
+
+    let mut messages = common::messages(&data_channel);
+    data_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+
+    session.add_ice_candidate(None).await.unwrap();
+    session.close().await.unwrap();
+    // the session is gone once deleted
+    assert_eq!(session.close().await, Err(rtc::Error::HttpRequestFailed));
+    peer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bearer_token_is_required() {
+    let (endpoint, _peers) = serve(Some("secret"));
+    let unauthorized = http::HttpSignalingClient::new(endpoint.clone());
+    assert_eq!(
+        connect(&unauthorized).await.err(),
+        Some(rtc::Error::HttpRequestFailed)
+    );
+    let wrong = http::HttpSignalingClient::new(endpoint.clone()).with_bearer_token("secreT");
+    assert_eq!(
+        connect(&wrong).await.err(),
+        Some(rtc::Error::HttpRequestFailed)
+    );
+
+    let authorized = http::HttpSignalingClient::new(endpoint).with_bearer_token("secret");
+    let (peer, _data_channel, session) = connect(&authorized).await.unwrap();
+    session.close().await.unwrap();
+    peer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn closed_peers_are_forgotten() {
+    let (endpoint, mut peers) = serve(None);
+    let client = http::HttpSignalingClient::new(endpoint);
+    let (peer, _data_channel, session) = connect(&client).await.unwrap();
+
+    let server_peer = common::timeout(peers.next()).await.unwrap();
+    server_peer.close().await.unwrap();
+    common::timeout(async {
+        while session.add_ice_candidate(None).await.is_ok() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    assert_eq!(session.close().await, Err(rtc::Error::HttpRequestFailed));
+    peer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oversized_requests_are_rejected() {
+    let (endpoint, _peers) = serve(None);
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&endpoint)
+        .header("Content-Type", "application/sdp")
+        .body(vec![b'a'; 65 * 1024])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,61 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::StreamExt;
+use unirtc::{self as rtc, sdp::Sdp};
+
+async fn ice_lite_peer() -> rtc::PeerConnection {
+    rtc::PeerConnection::new_with_settings(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            ice_lite: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_ice_connects_to_ice_lite() {
+    let client = common::peer().await;
+    let server = ice_lite_peer().await;
+    let (client_channel, server_channel) = common::connect(&client, &server).await;
+
+    let answer = Sdp::from_session_description(&server.local_description().await.unwrap()).unwrap();
+    assert!(answer.attribute("ice-lite").is_some());
+    assert!(!answer.candidates().is_empty());
+    for candidate in answer.candidates() {
+        assert_eq!(candidate.candidate_type, rtc::CandidateType::Host);
+    }
+    let offer = Sdp::from_session_description(&client.local_description().await.unwrap()).unwrap();
+    assert!(offer.attribute("ice-lite").is_none());
+
+    let mut messages = common::messages(&server_channel);
+    client_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+    let mut messages = common::messages(&client_channel);
+    server_channel.send(b"hello back").await.unwrap();
+    assert_eq!(
+        common::timeout(messages.next()).await.unwrap(),
+        b"hello back"
+    );
+
+    client.close().await.unwrap();
+    server.close().await.unwrap();
+}
+
+/// The ICE-lite peer may also be the one making the offer.
+#[tokio::test(flavor = "multi_thread")]
+async fn ice_lite_offers_to_full_ice() {
+    let server = ice_lite_peer().await;
+    let client = common::peer().await;
+    let (server_channel, client_channel) = common::connect(&server, &client).await;
+    let mut messages = common::messages(&client_channel);
+    server_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+
+    client.close().await.unwrap();
+    server.close().await.unwrap();
+}
@@ -0,0 +1,97 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc as rtc;
+
+fn ice_states(peer: &rtc::PeerConnection) -> mpsc::UnboundedReceiver<rtc::IceConnectionState> {
+    let (state_sender, states) = mpsc::unbounded();
+    peer.on_ice_connection_state_change(Box::new(move |state| {
+        _ = state_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+    states
+}
+
+async fn reach(
+    states: &mut mpsc::UnboundedReceiver<rtc::IceConnectionState>,
+    state: rtc::IceConnectionState,
+) {
+    common::timeout(async { while states.next().await != Some(state) {} }).await
+}
+
+async fn local_ufrag(peer: &rtc::PeerConnection) -> String {
+    let sdp = peer.local_description().await.unwrap().sdp();
+    sdp.lines()
+        .find_map(|line| line.strip_prefix("a=ice-ufrag:"))
+        .unwrap()
+        .to_owned()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn restart_keeps_data_channels() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let (local_channel, remote_channel) = common::connect(&offerer, &answerer).await;
+    let mut messages = common::messages(&remote_channel);
+    let ufrag = local_ufrag(&offerer).await;
+
+    let mut offerer_states = ice_states(&offerer);
+    let mut answerer_states = ice_states(&answerer);
+    offerer.restart_ice().unwrap();
+    common::negotiate(&offerer, &answerer).await;
+    assert_ne!(local_ufrag(&offerer).await, ufrag);
+    for states in [&mut offerer_states, &mut answerer_states] {
+        reach(states, rtc::IceConnectionState::Checking).await;
+        reach(states, rtc::IceConnectionState::Connected).await;
+    }
+
+    local_channel.send(b"after restart").await.unwrap();
+    assert_eq!(
+        common::timeout(messages.next()).await.unwrap(),
+        b"after restart"
+    );
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn restart_from_offer_options() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let (local_channel, remote_channel) = common::connect(&offerer, &answerer).await;
+    let mut messages = common::messages(&remote_channel);
+    let ufrag = local_ufrag(&offerer).await;
+
+    let mut states = ice_states(&offerer);
+    let offer = offerer
+        .create_offer_with_options(rtc::OfferOptions { ice_restart: true })
+        .await
+        .unwrap();
+    offerer.set_local_description(&offer).await.unwrap();
+    offerer
+        .wait_for_ice_gathering_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    let offer = offerer.local_description().await.unwrap();
+    answerer.set_remote_description(&offer).await.unwrap();
+    let answer = answerer
+        .create_answer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    offerer.set_remote_description(&answer).await.unwrap();
+    assert_ne!(local_ufrag(&offerer).await, ufrag);
+    reach(&mut states, rtc::IceConnectionState::Checking).await;
+    reach(&mut states, rtc::IceConnectionState::Connected).await;
+
+    local_channel.send(b"after restart").await.unwrap();
+    assert_eq!(
+        common::timeout(messages.next()).await.unwrap(),
+        b"after restart"
+    );
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
@@ -0,0 +1,133 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::time::Duration;
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc as rtc;
+
+fn listener(timeout: Duration) -> (rtc::RtcListener, mpsc::UnboundedSender<rtc::IncomingOffer>) {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )
+    .unwrap();
+    let (offer_sender, offers) = mpsc::unbounded();
+    let listener =
+        rtc::RtcListener::new(factory, Box::pin(offers)).with_handshake_timeout(Some(timeout));
+    (listener, offer_sender)
+}
+
+/// Sends the offer of `peer` to the listener and applies the answer.
+async fn offer(
+    peer: &rtc::PeerConnection,
+    offer_sender: &mpsc::UnboundedSender<rtc::IncomingOffer>,
+) {
+    let offer = peer
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+    offer_sender.unbounded_send(incoming_offer).unwrap();
+    let answer = answer_receiver.await.unwrap().unwrap();
+    peer.set_remote_description(&answer).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn accepted_data_channels_are_open() {
+    let (mut listener, offer_sender) = listener(common::TIMEOUT);
+    let client = common::peer().await;
+    let client_channel = client
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let mut client_messages = common::messages(&client_channel);
+    // handshakes only make progress while accepting
+    let (_, accepted) = common::timeout(futures::future::join(
+        offer(&client, &offer_sender),
+        listener.accept(),
+    ))
+    .await;
+    let accepted = accepted.unwrap();
+    drop(offer_sender);
+    assert_eq!(accepted.data_channels.len(), 1);
+    // sending fails on a channel that is not open yet
+    accepted.data_channels[0]
+        .data_channel
+        .send(b"welcome")
+        .await
+        .unwrap();
+    assert_eq!(
+        common::timeout(client_messages.next()).await.unwrap(),
+        b"welcome"
+    );
+    assert!(matches!(
+        common::timeout(listener.accept()).await,
+        Err(rtc::Error::ListenerClosed)
+    ));
+
+    client.close().await.unwrap();
+    accepted.peer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn later_data_channels_are_delivered() {
+    let (mut listener, offer_sender) = listener(common::TIMEOUT);
+    let client = common::peer().await;
+    let _first_channel = client
+        .create_data_channel("first", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let (_, accepted) = common::timeout(futures::future::join(
+        offer(&client, &offer_sender),
+        listener.accept(),
+    ))
+    .await;
+    let mut accepted = accepted.unwrap();
+
+    let second_channel = client
+        .create_data_channel("second", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    common::timeout(common::opened(&second_channel))
+        .await
+        .unwrap();
+    second_channel.send(b"hello").await.unwrap();
+    assert_eq!(accepted.data_channels.len(), 1);
+    let mut later = common::timeout(accepted.later_data_channels.next())
+        .await
+        .unwrap();
+    assert_eq!(
+        common::timeout(later.messages.next()).await.unwrap(),
+        b"hello"
+    );
+
+    client.close().await.unwrap();
+    accepted.peer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshakes_time_out() {
+    let (mut listener, offer_sender) = listener(Duration::from_millis(500));
+    let client = common::peer().await;
+    let _data_channel = client
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let offer = client
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    // the answer is never applied, so the peer cannot connect
+    let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+    offer_sender.unbounded_send(incoming_offer).unwrap();
+    drop(offer_sender);
+
+    assert!(matches!(
+        common::timeout(listener.accept()).await,
+        Err(rtc::Error::ListenerClosed)
+    ));
+    answer_receiver.await.unwrap().unwrap();
+    client.close().await.unwrap();
+}
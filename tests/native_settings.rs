@@ -0,0 +1,94 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::{net::IpAddr, sync::Arc};
+
+use unirtc::{self as rtc, sdp::Sdp};
+
+/// The candidates a peer created with `settings` gathers for its offer.
+async fn gathered(settings: rtc::NativeSettings) -> Vec<rtc::sdp::Candidate> {
+    let peer = rtc::PeerConnection::new_with_settings(&rtc::Configuration::default(), &settings)
+        .await
+        .unwrap();
+    let _data_channel = peer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let offer = peer
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    peer.close().await.unwrap();
+    Sdp::from_session_description(&offer).unwrap().candidates()
+}
+
+fn ip(candidate: &rtc::sdp::Candidate) -> IpAddr {
+    candidate.address.parse().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn host_candidates_use_the_port_range() {
+    let candidates = gathered(rtc::NativeSettings {
+        ephemeral_udp_port_range: Some(50000..=50100),
+        ..Default::default()
+    })
+    .await;
+    assert!(!candidates.is_empty());
+    for candidate in candidates {
+        assert!((50000..=50100).contains(&candidate.port), "{}", candidate);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn udp_mux_and_port_range_are_exclusive() {
+    let udp_mux = rtc::UdpMux::bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    let settings = rtc::NativeSettings {
+        udp_mux: Some(udp_mux.clone()),
+        ephemeral_udp_port_range: Some(50000..=50100),
+        ..Default::default()
+    };
+    assert!(matches!(
+        rtc::PeerConnection::new_with_settings(&rtc::Configuration::default(), &settings).await,
+        Err(rtc::Error::InvalidNativeSettings)
+    ));
+    udp_mux.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn filters_remove_candidates() {
+    let candidates = gathered(rtc::NativeSettings {
+        ip_filter: Some(Arc::new(|ip: IpAddr| ip.is_ipv4())),
+        ..Default::default()
+    })
+    .await;
+    assert!(!candidates.is_empty());
+    for candidate in &candidates {
+        assert!(ip(candidate).is_ipv4(), "{}", candidate);
+    }
+
+    let candidates = gathered(rtc::NativeSettings {
+        interface_filter: Some(Arc::new(|_: &str| false)),
+        ..Default::default()
+    })
+    .await;
+    assert!(candidates.is_empty(), "{:?}", candidates);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn nat_1to1_ips_replace_host_addresses() {
+    let external: IpAddr = "203.0.113.7".parse().unwrap();
+    let candidates = gathered(rtc::NativeSettings {
+        nat_1to1_ips: vec![external],
+        network_types: vec![rtc::NetworkType::Udp4],
+        ..Default::default()
+    })
+    .await;
+    assert!(!candidates.is_empty());
+    for candidate in candidates {
+        assert_eq!(candidate.candidate_type, rtc::CandidateType::Host);
+        assert_eq!(ip(&candidate), external, "{}", candidate);
+    }
+}
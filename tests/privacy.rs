@@ -0,0 +1,99 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use unirtc::{self as rtc, sdp::Sdp};
+
+async fn private_peer() -> rtc::PeerConnection {
+    rtc::PeerConnection::new(&rtc::Configuration {
+        ip_privacy: rtc::IpPrivacy::HideLocal,
+        ..Default::default()
+    })
+    .await
+    .unwrap()
+}
+
+fn trickled(peer: &rtc::PeerConnection) -> Arc<Mutex<Vec<String>>> {
+    let trickled = Arc::new(Mutex::new(vec![]));
+    let candidates = trickled.clone();
+    peer.on_ice_candidate(Box::new(move |ice_candidate| {
+        if let Some(ice_candidate) = ice_candidate {
+            candidates
+                .lock()
+                .unwrap()
+                .push(ice_candidate.to_init().unwrap().candidate);
+        }
+        Box::pin(async {})
+    }));
+    trickled
+}
+
+/// Fails if anything signaled for `description` or in `trickled` reveals an IP address.
+fn assert_private(description: &rtc::SessionDescription, trickled: &[String]) {
+    let sdp = Sdp::from_session_description(description).unwrap();
+    let mut candidates = sdp.candidates();
+    candidates.extend(trickled.iter().map(|candidate| candidate.parse().unwrap()));
+    assert!(!candidates.is_empty());
+    for candidate in &candidates {
+        assert!(!candidate.has_ip_address(), "{}", candidate);
+        assert!(
+            candidate
+                .related_address
+                .as_ref()
+                .is_none_or(|address| address == "0.0.0.0"),
+            "{}",
+            candidate
+        );
+    }
+    for line in description
+        .sdp()
+        .lines()
+        .filter(|line| line.starts_with("c="))
+    {
+        assert!(
+            line == "c=IN IP4 0.0.0.0" || line == "c=IN IP6 ::",
+            "{}",
+            line
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn signaling_reveals_no_ip_addresses() {
+    let offerer = private_peer().await;
+    let answerer = private_peer().await;
+    let offerer_trickled = trickled(&offerer);
+    let answerer_trickled = trickled(&answerer);
+    let mut remote_channels = common::data_channels(&answerer);
+    let local_channel = offerer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let open = common::opened(&local_channel);
+
+    let offer = offerer
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    assert_private(&offer, &offerer_trickled.lock().unwrap());
+    answerer.set_remote_description(&offer).await.unwrap();
+    let answer = answerer
+        .create_answer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    assert_private(&answer, &answerer_trickled.lock().unwrap());
+    offerer.set_remote_description(&answer).await.unwrap();
+
+    // the peers still connect through their mDNS host candidates
+    common::timeout(open).await.unwrap();
+    let remote_channel = common::timeout(remote_channels.next()).await.unwrap();
+    let mut messages = common::messages(&remote_channel);
+    local_channel.send(b"hello").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"hello");
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
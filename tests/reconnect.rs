@@ -0,0 +1,108 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use unirtc as rtc;
+
+fn send_offer(offer_sender: mpsc::UnboundedSender<rtc::IncomingOffer>) -> rtc::SendOfferFn {
+    Box::new(move |offer| {
+        let offer_sender = offer_sender.clone();
+        Box::pin(async move {
+            let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+            offer_sender
+                .unbounded_send(incoming_offer)
+                .map_err(|_| rtc::Error::SignalingClosed)?;
+            answer_receiver
+                .await
+                .map_err(|_| rtc::Error::SignalingClosed)?
+        })
+    })
+}
+
+/// A connection that is dropped without a word only ever becomes disconnected here, as it
+/// takes a minute to fail.
+#[tokio::test(flavor = "multi_thread")]
+async fn reconnects_when_disconnected() {
+    let server_factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )
+    .unwrap();
+    let client_factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            ice_timeouts: rtc::IceTimeouts {
+                disconnected: Some(Duration::from_secs(1)),
+                failed: Some(Duration::from_secs(60)),
+                keepalive_interval: Some(Duration::from_millis(200)),
+            },
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let (offer_sender, offers) = mpsc::unbounded();
+    let mut listener = rtc::RtcListener::new(server_factory, Box::pin(offers));
+
+    let server = async move {
+        let accepted = listener.accept().await.unwrap();
+        accepted.peer.close().await.unwrap();
+        let accepted = listener.accept().await.unwrap();
+        accepted.data_channels[0]
+            .data_channel
+            .send(b"welcome back")
+            .await
+            .unwrap();
+        // the client closing ends the offer stream
+        assert!(matches!(
+            listener.accept().await,
+            Err(rtc::Error::ListenerClosed)
+        ));
+        accepted.peer.close().await.unwrap();
+    };
+    let client = async move {
+        let peer = rtc::ReconnectingPeer::new(client_factory, send_offer(offer_sender))
+            .with_data_channel("data", rtc::DataChannelInit::default())
+            .with_backoff(rtc::Backoff {
+                initial_delay: Duration::from_millis(100),
+                max_attempts: Some(5),
+                ..Default::default()
+            })
+            .with_disconnect_timeout(Some(Duration::from_millis(500)))
+            .connect()
+            .await
+            .unwrap();
+        let mut events = vec![];
+        loop {
+            match peer.next_event().await.unwrap() {
+                rtc::ReconnectEvent::Message { data, .. } => {
+                    assert_eq!(data, b"welcome back");
+                    break;
+                }
+                rtc::ReconnectEvent::Reconnecting { attempt, .. } => {
+                    events.push(rtc::ReconnectEvent::Reconnecting {
+                        attempt,
+                        delay: Duration::ZERO,
+                    });
+                }
+                event => events.push(event),
+            }
+        }
+        assert_eq!(
+            events,
+            [
+                rtc::ReconnectEvent::Disconnected,
+                rtc::ReconnectEvent::Reconnecting {
+                    attempt: 1,
+                    delay: Duration::ZERO
+                },
+                rtc::ReconnectEvent::Reconnected { attempts: 1 },
+            ]
+        );
+        assert!(peer.is_connected());
+        peer.close().await.unwrap();
+    };
+    common::timeout(futures::future::join(server, client)).await;
+}
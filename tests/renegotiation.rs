@@ -0,0 +1,54 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc as rtc;
+
+fn negotiations_needed(peer: &rtc::PeerConnection) -> mpsc::UnboundedReceiver<()> {
+    let (negotiation_sender, negotiations) = mpsc::unbounded();
+    peer.on_negotiation_needed(Box::new(move || {
+        _ = negotiation_sender.unbounded_send(());
+        Box::pin(async {})
+    }));
+    negotiations
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn data_channel_added_mid_session() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let mut negotiations = negotiations_needed(&offerer);
+    let mut remote_channels = common::data_channels(&answerer);
+    let mut states = common::states(&offerer);
+
+    let first = offerer
+        .create_data_channel("first", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let first_open = common::opened(&first);
+    common::timeout(negotiations.next()).await.unwrap();
+    common::negotiate(&offerer, &answerer).await;
+    common::reach(&mut states, rtc::PeerConnectionState::Connected).await;
+    common::timeout(first_open).await.unwrap();
+    common::timeout(remote_channels.next()).await.unwrap();
+
+    let second = offerer
+        .create_data_channel("second", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let second_open = common::opened(&second);
+    common::negotiate(&offerer, &answerer).await;
+    common::timeout(second_open).await.unwrap();
+    let remote_second = common::timeout(remote_channels.next()).await.unwrap();
+    let mut messages = common::messages(&remote_second);
+    second.send(b"second").await.unwrap();
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"second");
+    assert_eq!(
+        offerer.connection_state(),
+        rtc::PeerConnectionState::Connected
+    );
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
@@ -0,0 +1,59 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use unirtc::{self as rtc, sdp::Sdp};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn webrtc_rs_descriptions_round_trip() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let (_local_channel, _remote_channel) = common::connect(&offerer, &answerer).await;
+
+    for description in [
+        offerer.local_description().await.unwrap(),
+        offerer.remote_description().await.unwrap(),
+    ] {
+        let sdp = Sdp::from_session_description(&description).unwrap();
+        assert_eq!(sdp.to_string(), description.sdp());
+        assert!(!sdp.candidates().is_empty());
+        assert!(sdp.fingerprint().is_some());
+    }
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn munged_offers_are_accepted() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    let mut remote_channels = common::data_channels(&answerer);
+    let local_channel = offerer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let open = common::opened(&local_channel);
+
+    let offer = offerer
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    let mut sdp = Sdp::from_session_description(&offer).unwrap();
+    sdp.media[0].set_max_message_size(65536);
+    let offer = sdp.to_session_description(rtc::SdpType::Offer).unwrap();
+    answerer.set_remote_description(&offer).await.unwrap();
+    let answer = answerer
+        .create_answer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    offerer.set_remote_description(&answer).await.unwrap();
+
+    common::timeout(open).await.unwrap();
+    common::timeout(futures::StreamExt::next(&mut remote_channels))
+        .await
+        .unwrap();
+
+    offerer.close().await.unwrap();
+    answerer.close().await.unwrap();
+}
@@ -0,0 +1,106 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc as rtc;
+
+fn server() -> (rtc::Server, mpsc::UnboundedSender<rtc::IncomingOffer>) {
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings::default(),
+    )
+    .unwrap();
+    let (offer_sender, offers) = mpsc::unbounded();
+    let server = rtc::Server::new(rtc::RtcListener::new(factory, Box::pin(offers)));
+    (server, offer_sender)
+}
+
+/// A client with one data channel, which sends `hello` as soon as the channel opens.
+async fn client(
+    offer_sender: &mpsc::UnboundedSender<rtc::IncomingOffer>,
+) -> (
+    rtc::PeerConnection,
+    rtc::DataChannel,
+    mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    let peer = common::peer().await;
+    let data_channel = peer
+        .create_data_channel("data", rtc::DataChannelInit::default())
+        .await
+        .unwrap();
+    let messages = common::messages(&data_channel);
+    let sender = data_channel.clone();
+    data_channel.on_open(Box::new(move || {
+        let sender = sender.clone();
+        Box::pin(async move {
+            sender.send(b"hello").await.unwrap();
+        })
+    }));
+    let offer = peer
+        .create_offer_complete(Some(common::TIMEOUT))
+        .await
+        .unwrap();
+    let (incoming_offer, answer_receiver) = rtc::IncomingOffer::new(offer);
+    offer_sender.unbounded_send(incoming_offer).unwrap();
+    let answer = answer_receiver.await.unwrap().unwrap();
+    peer.set_remote_description(&answer).await.unwrap();
+    (peer, data_channel, messages)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keeps_messages_sent_while_connecting() {
+    let (server, offer_sender) = server();
+    let server = Arc::new(server);
+    let events = {
+        let server = server.clone();
+        tokio::spawn(async move {
+            let mut events = vec![];
+            while let Ok(event) = server.next_event().await {
+                if let rtc::ServerEvent::Connected(client_id) = event {
+                    // the channel is open once the client is connected
+                    server.send(client_id, b"welcome").await.unwrap();
+                }
+                events.push(event);
+            }
+            events
+        })
+    };
+    let (peer, _data_channel, mut messages) = common::timeout(client(&offer_sender)).await;
+    drop(offer_sender);
+    assert_eq!(common::timeout(messages.next()).await.unwrap(), b"welcome");
+    peer.close().await.unwrap();
+
+    let client_id = rtc::ClientId(0);
+    assert_eq!(
+        common::timeout(events).await.unwrap(),
+        [
+            rtc::ServerEvent::Connected(client_id),
+            rtc::ServerEvent::Message {
+                client_id,
+                data: b"hello".to_vec()
+            },
+            rtc::ServerEvent::Disconnected(client_id),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn close_does_not_wait_for_next_event() {
+    let (server, offer_sender) = server();
+    let server = Arc::new(server);
+    let next_event = {
+        let server = server.clone();
+        tokio::spawn(async move { server.next_event().await })
+    };
+    // let the pending call take hold of the listener
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    common::timeout(server.close()).await;
+    assert!(matches!(
+        common::timeout(next_event).await.unwrap(),
+        Err(rtc::Error::ListenerClosed)
+    ));
+    drop(offer_sender);
+}
@@ -0,0 +1,100 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::{channel::mpsc, StreamExt};
+use unirtc as rtc;
+
+/// Collects events from `receiver` until `last` is seen.
+async fn events_until<T: PartialEq + Copy>(
+    receiver: &mut mpsc::UnboundedReceiver<T>,
+    last: T,
+) -> Vec<T> {
+    common::timeout(async {
+        let mut events = vec![];
+        while let Some(event) = receiver.next().await {
+            events.push(event);
+            if event == last {
+                break;
+            }
+        }
+        events
+    })
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn state_changes_are_reported() {
+    let offerer = common::peer().await;
+    let answerer = common::peer().await;
+    assert_eq!(offerer.signaling_state(), rtc::SignalingState::Stable);
+    assert_eq!(offerer.ice_gathering_state(), rtc::IceGatheringState::New);
+    assert_eq!(offerer.ice_connection_state(), rtc::IceConnectionState::New);
+
+    let (signaling_sender, mut signaling_states) = mpsc::unbounded();
+    offerer.on_signaling_state_change(Box::new(move |state| {
+        _ = signaling_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+    let (gathering_sender, mut gathering_states) = mpsc::unbounded();
+    offerer.on_ice_gathering_state_change(Box::new(move |state| {
+        _ = gathering_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+    let (ice_connection_sender, mut ice_connection_states) = mpsc::unbounded();
+    offerer.on_ice_connection_state_change(Box::new(move |state| {
+        _ = ice_connection_sender.unbounded_send(state);
+        Box::pin(async {})
+    }));
+    common::connect(&offerer, &answerer).await;
+
+    assert_eq!(
+        events_until(&mut signaling_states, rtc::SignalingState::Stable).await,
+        [
+            rtc::SignalingState::HaveLocalOffer,
+            rtc::SignalingState::Stable
+        ]
+    );
+    assert_eq!(offerer.signaling_state(), rtc::SignalingState::Stable);
+    assert_eq!(
+        events_until(&mut gathering_states, rtc::IceGatheringState::Complete).await,
+        [
+            rtc::IceGatheringState::Gathering,
+            rtc::IceGatheringState::Complete
+        ]
+    );
+    assert_eq!(
+        offerer.ice_gathering_state(),
+        rtc::IceGatheringState::Complete
+    );
+    let ice_connection_events = events_until(
+        &mut ice_connection_states,
+        rtc::IceConnectionState::Connected,
+    )
+    .await;
+    assert_eq!(
+        ice_connection_events.last(),
+        Some(&rtc::IceConnectionState::Connected),
+        "{:?}",
+        ice_connection_events
+    );
+    assert!(
+        ice_connection_events.contains(&rtc::IceConnectionState::Checking),
+        "{:?}",
+        ice_connection_events
+    );
+    assert_eq!(
+        offerer.ice_connection_state(),
+        rtc::IceConnectionState::Connected
+    );
+
+    offerer.close().await.unwrap();
+    assert_eq!(offerer.signaling_state(), rtc::SignalingState::Closed);
+    assert_eq!(
+        events_until(&mut ice_connection_states, rtc::IceConnectionState::Closed)
+            .await
+            .last(),
+        Some(&rtc::IceConnectionState::Closed)
+    );
+    answerer.close().await.unwrap();
+}
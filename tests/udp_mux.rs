@@ -0,0 +1,56 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use futures::StreamExt;
+use unirtc::{self as rtc, sdp::Sdp};
+
+const CLIENTS: usize = 3;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn peers_share_one_port() {
+    let udp_mux = rtc::UdpMux::bind("0.0.0.0:0".parse().unwrap())
+        .await
+        .unwrap();
+    let port = udp_mux.local_address().unwrap().port();
+    let factory = rtc::PeerFactory::new(
+        &rtc::Configuration::default(),
+        &rtc::NativeSettings {
+            udp_mux: Some(udp_mux.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let mut connections = vec![];
+    for _ in 0..CLIENTS {
+        let client = common::peer().await;
+        let server = factory.create().await.unwrap();
+        let (client_channel, server_channel) = common::connect(&client, &server).await;
+        let sdp =
+            Sdp::from_session_description(&server.local_description().await.unwrap()).unwrap();
+        let candidates = sdp.candidates();
+        assert!(!candidates.is_empty());
+        for candidate in candidates {
+            assert_eq!(candidate.port, port, "{}", candidate);
+        }
+        connections.push((client, server, client_channel, server_channel));
+    }
+
+    // every connection stays usable while the others are open
+    for (index, (_, _, client_channel, server_channel)) in connections.iter().enumerate() {
+        let mut messages = common::messages(server_channel);
+        let message = format!("hello from client {}", index);
+        client_channel.send(message.as_bytes()).await.unwrap();
+        assert_eq!(
+            common::timeout(messages.next()).await.unwrap(),
+            message.as_bytes()
+        );
+    }
+
+    for (client, server, _, _) in connections {
+        client.close().await.unwrap();
+        server.close().await.unwrap();
+    }
+    udp_mux.close().await.unwrap();
+}